@@ -0,0 +1,69 @@
+#![allow(non_snake_case)]
+
+// Embeds goto's resolver in another program: the store lives in memory, time
+// is fixed, and duplicate paths are answered by code instead of a terminal.
+//
+//     cargo run --example embed_resolver -- proj/src
+
+use anyhow::Result;
+use goto::clock::FixedClock;
+use goto::paths::ConfigPaths;
+use goto::prompt::{DuplicateChoice, Prompter};
+use goto::storage::MemoryStorage;
+use goto::store::{AddBehavior, Store, StoreOptions};
+use std::path::Path;
+use std::sync::Arc;
+
+// Saves a second keyword for an already-saved path as an alias.
+struct AliasDuplicates;
+
+impl Prompter for AliasDuplicates {
+    fn ConfirmDuplicatePath(&self, _: &Path, _: &str, _: &[String]) -> Result<DuplicateChoice> {
+        Ok(DuplicateChoice::Alias)
+    }
+
+    fn Warn(&self, message: &str) {
+        eprintln!("goto: {message}");
+    }
+}
+
+fn main() -> Result<()> {
+    let storage = Arc::new(MemoryStorage::default());
+
+    let paths = ConfigPaths::InDir(Path::new("/goto"));
+
+    let mut store = Store::LoadWith(
+        paths.clone(),
+        StoreOptions {
+            storage: storage.clone(),
+            clock: Arc::new(FixedClock(1_700_000_000)),
+            prompter: Arc::new(AliasDuplicates),
+            ..StoreOptions::default()
+        },
+    )?;
+
+    let behavior = AddBehavior {
+        force: false,
+        assumeYes: false,
+        hostOnly: false,
+        local: false,
+    };
+
+    let project = std::env::current_dir()?;
+
+    store.AddShortcut("proj", &project, None, &behavior)?;
+
+    store.AddShortcut("here", &project, None, &behavior)?;
+
+    let query = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "here".to_string());
+
+    let resolved = store.ResolveJump(&query)?;
+
+    println!("{query} -> {}", resolved.targetPath.display());
+
+    print!("{}", storage.Contents(&paths.aliasFile).unwrap_or_default());
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, Parser, ValueEnum};
 use clap_complete::Shell;
 
 #[derive(Parser, Debug)]
@@ -30,6 +30,12 @@ pub struct CliArgs {
     #[arg(short = 'f', long = "force", action = ArgAction::SetTrue, help = "Replace an existing keyword or overwrite duplicate paths without prompting.")]
     pub addForce: bool,
 
+    #[arg(long = "host-only", action = ArgAction::SetTrue, requires = "add", help = "Record the --add mapping for the current host only (hostname or GOTO_HOST).")]
+    pub hostOnly: bool,
+
+    #[arg(long = "local", action = ArgAction::SetTrue, requires = "add", help = "Override a read-only shortcut from an @include file with a personal --add mapping.")]
+    pub local: bool,
+
     #[arg(
         short = 'c',
         long = "copy",
@@ -39,6 +45,69 @@ pub struct CliArgs {
     )]
     pub copy: Option<Vec<String>>,
 
+    #[arg(
+        long = "alias",
+        num_args = 2,
+        value_names = ["ALIAS", "KEYWORD"],
+        help = "Add ALIAS as an alternate name for an existing KEYWORD."
+    )]
+    pub alias: Option<Vec<String>>,
+
+    #[arg(
+        long = "rename",
+        num_args = 2,
+        value_names = ["OLD", "NEW"],
+        help = "Rename a keyword or alias, keeping its aliases and usage history."
+    )]
+    pub rename: Option<Vec<String>>,
+
+    #[arg(
+        long = "anchor",
+        num_args = 2..=3,
+        value_names = ["KEYWORD", "NAME", "SUBPATH"],
+        help = "Name a subpath inside KEYWORD so `to KEYWORD:NAME` jumps there. Omit SUBPATH to remove the anchor."
+    )]
+    pub anchor: Option<Vec<String>>,
+
+    #[arg(
+        long = "env",
+        num_args = 2..=3,
+        value_names = ["KEYWORD", "NAME", "VALUE"],
+        help = "Export NAME=VALUE when jumping into KEYWORD through the wrapper. Omit VALUE to remove it."
+    )]
+    pub env: Option<Vec<String>>,
+
+    #[arg(
+        long = "on-enter",
+        num_args = 1..=2,
+        value_names = ["KEYWORD", "COMMAND"],
+        help = "Run COMMAND in your shell after jumping into KEYWORD. Omit COMMAND to clear it."
+    )]
+    pub onEnter: Option<Vec<String>>,
+
+    #[arg(
+        long = "on-leave",
+        num_args = 1..=2,
+        value_names = ["KEYWORD", "COMMAND"],
+        help = "Run COMMAND in your shell when jumping out of KEYWORD. Omit COMMAND to clear it."
+    )]
+    pub onLeave: Option<Vec<String>>,
+
+    #[arg(
+        long = "trust",
+        value_name = "KEYWORD",
+        help = "Approve KEYWORD's environment and on-enter/on-leave commands."
+    )]
+    pub trust: Option<String>,
+
+    #[arg(
+        long = "default-subpath",
+        num_args = 1..=2,
+        value_names = ["KEYWORD", "SUBPATH"],
+        help = "Make plain `to KEYWORD` land in SUBPATH (`to KEYWORD/` still opens the root). Omit SUBPATH to clear it."
+    )]
+    pub defaultSubpath: Option<Vec<String>>,
+
     #[arg(
         short = 'r',
         long = "rm",
@@ -91,8 +160,23 @@ pub struct CliArgs {
     )]
     pub listMaxDepth: Option<usize>,
 
-    #[arg(short = 'j', long = "json", action = ArgAction::SetTrue, help = "Return list/search results as JSON.")]
-    pub listJson: bool,
+    #[arg(long = "visited", action = ArgAction::SetTrue, help = "List frequently visited directories that have no shortcut yet (with --list).")]
+    pub listVisited: bool,
+
+    #[arg(long = "hosts", action = ArgAction::SetTrue, help = "Show per-host path overrides alongside each shortcut (with --list).")]
+    pub listHosts: bool,
+
+    #[arg(long = "portable", action = ArgAction::SetTrue, help = "Show paths in their stored `~`/`$ROOT` form (with --list).")]
+    pub listPortable: bool,
+
+    #[arg(long = "resolvers", action = ArgAction::SetTrue, help = "List the external resolver plugins consulted when a target matches no shortcut.")]
+    pub listResolvers: bool,
+
+    #[arg(long = "migrate-paths", action = ArgAction::SetTrue, help = "Rewrite saved absolute paths under $HOME or a portable root into `~`/`$ROOT` form.")]
+    pub migratePaths: bool,
+
+    #[arg(short = 'j', long = "json", action = ArgAction::SetTrue, help = "Print the result of any command as JSON (versioned schema, see README), including errors.")]
+    pub json: bool,
 
     #[arg(
         short = 'n',
@@ -102,13 +186,68 @@ pub struct CliArgs {
     )]
     pub listLimit: Option<usize>,
 
+    #[arg(
+        long = "which",
+        num_args = 0..=1,
+        value_name = "PATH",
+        default_missing_value = "",
+        help = "Print the shortcut containing PATH (default: current directory) as keyword/remainder."
+    )]
+    pub which: Option<String>,
+
+    #[arg(long = "all", action = ArgAction::SetTrue, requires = "which", help = "List every shortcut containing the path, deepest first (with --which).")]
+    pub whichAll: bool,
+
+    #[arg(
+        long = "format",
+        value_name = "TEMPLATE",
+        help = "Output template for --list ({keyword}, {path}, {exists}, {last-used}, {uses}, {expires}, {tags}) or --which ({keyword}, {remainder}, {path}, {target}); \\t, \\n and \\0 are expanded."
+    )]
+    pub format: Option<String>,
+
+    #[arg(
+        long = "columns",
+        value_name = "COLUMNS",
+        value_delimiter = ',',
+        help = "Columns for --list: keyword, path, exists, last-used, uses, expires, tags (comma-separated)."
+    )]
+    pub listColumns: Vec<ListColumn>,
+
+    #[arg(
+        long = "output",
+        value_name = "FORMAT",
+        help = "Layout for --list: table (aligned to the terminal width), tsv, csv, or nul (NUL-terminated records)."
+    )]
+    pub listOutput: Option<ListOutput>,
+
+    #[arg(long = "history", action = ArgAction::SetTrue, help = "List this shell session's directory history (falls back to the global jump log).")]
+    pub history: bool,
+
     #[arg(short = 'p', long = "print-path", action = ArgAction::SetTrue, help = "Print the resolved path for TARGET without changing directory.")]
     pub printPath: bool,
 
-    #[arg(short = 'u', long = "cursor", action = ArgAction::SetTrue, help = "Open the target in Cursor after jumping.")]
+    #[arg(
+        long = "open",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        value_name = "NAME",
+        help = "Open the target with launcher NAME after jumping (default: the shortcut's launcher, then default_launcher)."
+    )]
+    pub open: Option<String>,
+
+    #[arg(
+        long = "launcher",
+        num_args = 1..=2,
+        value_names = ["KEYWORD", "NAME"],
+        help = "Set the launcher `--open` uses for KEYWORD. Omit NAME to clear it."
+    )]
+    pub launcher: Option<Vec<String>>,
+
+    #[arg(short = 'u', long = "cursor", action = ArgAction::SetTrue, help = "Open the target in Cursor after jumping (preset for --open=cursor).")]
     pub cursor: bool,
 
-    #[arg(short = 'C', long = "code", action = ArgAction::SetTrue, help = "Open the target in VS Code after jumping.")]
+    #[arg(short = 'C', long = "code", action = ArgAction::SetTrue, help = "Open the target in VS Code after jumping (preset for --open=code).")]
     pub code: bool,
 
     #[arg(short = 'N', long = "no-create", action = ArgAction::SetTrue, help = "Fail instead of creating missing directories on jump.")]
@@ -118,10 +257,26 @@ pub struct CliArgs {
         short = 's',
         long = "sort",
         value_name = "MODE",
-        help = "Set sorting mode: added | alpha | recent."
+        help = "Sorting mode for this invocation only: added | alpha | recent | nearby (ranked by distance from the current directory). Use --config sort_order MODE to change the default."
     )]
     pub sortMode: Option<String>,
 
+    #[arg(
+        long = "order-by",
+        value_name = "KEYS",
+        allow_hyphen_values = true,
+        help = "Order --list results by comma-separated keys: keyword, path, added, recent, expiry, uses, nearby; prefix a key with - to reverse it (e.g. path,-recent)."
+    )]
+    pub listOrderBy: Option<String>,
+
+    #[arg(
+        long = "config",
+        value_name = "KEY [VALUE]",
+        num_args = 0..=2,
+        help = "Show persisted settings, print one KEY, or set KEY to VALUE (an empty VALUE removes it)."
+    )]
+    pub config: Option<Vec<String>>,
+
     #[arg(long = "show-sort", action = ArgAction::SetTrue, help = "Print the current sorting mode.")]
     pub showSortMode: bool,
 
@@ -133,6 +288,14 @@ pub struct CliArgs {
     )]
     pub expire: Option<u64>,
 
+    #[arg(
+        long = "export-shell",
+        value_enum,
+        value_name = "SHELL",
+        help = "Print shell code exposing shortcuts as named directories (zsh), variables, and optionally CDPATH."
+    )]
+    pub exportShell: Option<ExportShell>,
+
     #[arg(
         long = "completions",
         visible_alias = "generate-completions",
@@ -165,6 +328,19 @@ pub struct CliArgs {
     #[arg(long = "install-wrapper-force", action = ArgAction::SetTrue, help = "Overwrite existing goto wrapper when using --install-wrapper.")]
     pub installWrapperForce: bool,
 
+    #[arg(long = "install-wrapper-track", action = ArgAction::SetTrue, requires = "installWrapper", help = "Also install a chpwd/PROMPT_COMMAND hook that records directory visits for --suggest.")]
+    pub installWrapperTrack: bool,
+
+    #[arg(
+        long = "track",
+        value_name = "PATH",
+        help = "Record a visit to PATH (called from the shell hook installed by --install-wrapper-track)."
+    )]
+    pub track: Option<String>,
+
+    #[arg(long = "suggest", action = ArgAction::SetTrue, help = "Suggest shortcuts for frequently visited directories that have none yet.")]
+    pub suggest: bool,
+
     #[arg(long = "__check-wrapper", hide = true)]
     pub checkWrapper: Option<String>,
 
@@ -180,10 +356,40 @@ pub struct CliArgs {
     #[arg(long = "no-color", action = ArgAction::SetTrue, help = "Disable colored output.")]
     pub noColor: bool,
 
-    #[arg(value_name = "TARGET")]
+    #[arg(
+        value_name = "TARGET",
+        allow_negative_numbers = true,
+        help = "Keyword[/subpath], filesystem path, or - / -N to go back in history."
+    )]
     pub target: Option<String>,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportShell {
+    Zsh,
+    Bash,
+    Fish,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListColumn {
+    Keyword,
+    Path,
+    Exists,
+    LastUsed,
+    Uses,
+    Expires,
+    Tags,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListOutput {
+    Table,
+    Tsv,
+    Csv,
+    Nul,
+}
+
 pub fn ParseArgs() -> Result<CliArgs> {
     let args = CliArgs::parse();
 
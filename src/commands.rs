@@ -1,22 +1,37 @@
-use crate::cli::CliArgs;
+use crate::cli::{CliArgs, ExportShell, ListColumn, ListOutput};
+use crate::clock::{Clock, SystemClock};
+use crate::completion;
+use crate::error::GotoError;
+use crate::export::{self, EXPORTED_VAR, ShellQuote};
+use crate::hooks::{HookEvent, ScriptHooks};
 use crate::output;
 use crate::paths::ConfigPaths;
-use crate::store::{AddBehavior, SearchMode, SearchOptions, Store};
+use crate::prompt::TerminalPrompter;
+use crate::resolvers::ResolverPolicy;
+use crate::storage::FileStorage;
+use crate::store::{
+    self, AddBehavior, HookKind, OrderKey, ParseOrderBy, ParseSortMode, ResolvedJump, SearchMode,
+    SearchOptions, Store, StoreOptions,
+};
 use anyhow::{Context, Result, bail};
 use clap::CommandFactory;
 use clap_complete::{Shell, generate};
 use glob::Pattern;
 use regex::RegexBuilder;
+use serde_json::{Value, json};
 use std::env;
 use std::fs;
 use std::io::{IsTerminal, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 
 pub enum Action {
     Help,
     ShowSort,
+    History {
+        outputJson: bool,
+    },
     Add {
         keyword: String,
         path: PathBuf,
@@ -29,6 +44,40 @@ pub enum Action {
         existing: String,
         newValue: String,
     },
+    Alias {
+        alias: String,
+        keyword: String,
+    },
+    Rename {
+        existing: String,
+        newKeyword: String,
+    },
+    Anchor {
+        keyword: String,
+        name: String,
+        subpath: Option<String>,
+    },
+    DefaultSubpath {
+        keyword: String,
+        subpath: Option<String>,
+    },
+    Launcher {
+        keyword: String,
+        name: Option<String>,
+    },
+    Env {
+        keyword: String,
+        name: String,
+        value: Option<String>,
+    },
+    Hook {
+        keyword: String,
+        kind: HookKind,
+        command: Option<String>,
+    },
+    Trust {
+        keyword: String,
+    },
     Remove {
         keyword: String,
     },
@@ -38,11 +87,29 @@ pub enum Action {
     InstallWrapper {
         rcPath: Option<String>,
         force: bool,
+        track: bool,
+    },
+    Suggest,
+    MigratePaths,
+    ExportShell {
+        shell: ExportShell,
+    },
+    Which {
+        path: PathBuf,
+        all: bool,
+        format: Option<String>,
+        outputJson: bool,
+    },
+    Resolvers {
+        outputJson: bool,
+    },
+    Config {
+        key: Option<String>,
+        value: Option<String>,
     },
     Jump {
         target: String,
-        runCursor: bool,
-        runCode: bool,
+        launcher: Option<String>,
         create: bool,
     },
     Complete {
@@ -60,6 +127,13 @@ pub enum Action {
         limit: Option<usize>,
         within: Option<PathBuf>,
         maxDepth: Option<usize>,
+        visited: bool,
+        portable: bool,
+        hosts: bool,
+        orderBy: Vec<OrderKey>,
+        columns: Vec<ListColumn>,
+        layout: Option<ListOutput>,
+        format: Option<String>,
     },
     CheckWrapper {
         rcPath: String,
@@ -70,8 +144,9 @@ pub fn Execute(args: CliArgs) -> Result<()> {
     if args.classifyInvocation {
         let action = DetermineAction(&args)?;
 
+        // JSON results are printed rather than followed by a cd in the wrapper.
         match action {
-            Action::Jump { .. } => println!("jump"),
+            Action::Jump { .. } if !args.json => println!("jump"),
             _ => println!("nojump"),
         }
 
@@ -81,13 +156,11 @@ pub fn Execute(args: CliArgs) -> Result<()> {
     if let Some(rcPath) = args.checkWrapper.as_ref() {
         let path = PathBuf::from(rcPath);
 
-        let present = WrapperPresent(&path)?;
-
-        if present {
-            std::process::exit(0);
+        if WrapperPresent(&path)? {
+            return Ok(());
         }
 
-        std::process::exit(1);
+        bail!(GotoError::NoMatch);
     }
 
     if let Some(shell) = args.generateCompletions {
@@ -106,35 +179,92 @@ pub fn Execute(args: CliArgs) -> Result<()> {
 
     let paths = ConfigPaths::Resolve()?;
 
+    if let Some(path) = args.track.as_ref() {
+        return TrackVisit(paths, path);
+    }
+
+    // Prompts call `--which` on every render: answer it before the legacy
+    // check (which spawns zsh) and without loading the whole store.
+    if args.which.is_some() {
+        if let Action::Which {
+            path,
+            all,
+            format,
+            outputJson,
+        } = DetermineAction(&args)?
+        {
+            return WhichShortcut(&paths, &path, all, format.as_deref(), outputJson);
+        }
+    }
+
     let skipLegacyCheck = matches!(env::var("GOTO_SKIP_LEGACY_CHECK"), Ok(val) if val == "1");
 
     if !skipLegacyCheck && LegacyToDetected()? {
-        eprintln!(
+        bail!(GotoError::Failed(
             "Detected a legacy Zsh `to` function (likely from to-zsh). Disable it before running goto."
-        );
-        std::process::exit(1);
+                .to_string()
+        ));
     }
 
-    let mut store = Store::Load(paths)?;
+    let mut store = Store::LoadWith(
+        paths,
+        StoreOptions {
+            prompter: Arc::new(TerminalPrompter),
+            hooks: Arc::new(ScriptHooks),
+            resolvers: ResolverPolicy::Enabled,
+            ..StoreOptions::default()
+        },
+    )?;
 
     let addBehavior = AddBehavior {
         force: args.addForce,
         assumeYes: matches!(std::env::var("GOTO_ASSUME_YES"), Ok(val) if val == "1"),
+        hostOnly: args.hostOnly,
+        local: args.local,
     };
 
     if let Some(mode) = args.sortMode.as_deref() {
-        store.SetSortMode(mode)?;
-        output::PrintSortMode(mode);
+        store.sortMode = ParseSortMode(mode)?;
     }
 
     if args.showSortMode {
-        output::PrintCurrentSortMode(&store.sortMode);
+        if args.json {
+            output::PrintResultJson("show-sort", json!({ "sort": store.sortMode.Value() }))?;
+        } else {
+            output::PrintCurrentSortMode(&store.sortMode);
+        }
+
         return Ok(());
     }
 
+    let asJson = args.json;
+
     let action = DetermineAction(&args)?;
 
+    // `--sort` orders this invocation's listing; anywhere else it would be
+    // silently ignored.
+    if args.sortMode.is_some() && !matches!(action, Action::Search { .. } | Action::Complete { .. })
+    {
+        bail!(GotoError::Usage("--sort only orders --list and completion output for this invocation. Use `to --config sort_order MODE` to change the default.".to_string()));
+    }
+
+    if !matches!(
+        action,
+        Action::Search { .. }
+            | Action::Complete { .. }
+            | Action::History { .. }
+            | Action::Resolvers { .. }
+            | Action::Help
+    ) {
+        store.PruneExpired()?;
+    }
+
     match action {
+        Action::Help if asJson => {
+            bail!(GotoError::Usage(
+                "--json needs a command or a jump target.".to_string()
+            ));
+        }
         Action::Help => {
             let mut cmd = CliArgs::command();
             cmd.print_help()?;
@@ -142,11 +272,28 @@ pub fn Execute(args: CliArgs) -> Result<()> {
 
             output::PrintSavedShortcuts(&store);
         }
-        Action::InstallWrapper { rcPath, force } => {
-            let rcPath = rcPath.unwrap_or_else(|| DetectShellRc());
+        Action::InstallWrapper {
+            rcPath,
+            force,
+            track,
+        } => {
+            let rcPath = rcPath.unwrap_or_else(DetectShellRc);
             let rcPath = PathBuf::from(rcPath);
 
-            let result = InstallWrapper(&rcPath, force)?;
+            let result = InstallWrapper(&rcPath, force, track)?;
+
+            if asJson {
+                let status = match result {
+                    WrapperAction::Added => "added",
+                    WrapperAction::Updated => "updated",
+                    WrapperAction::Skipped => "skipped",
+                };
+
+                return output::PrintResultJson(
+                    "install-wrapper",
+                    json!({ "rc": rcPath, "status": status }),
+                );
+            }
 
             match result {
                 WrapperAction::Added => println!("Wrapper added to {}", rcPath.display()),
@@ -167,6 +314,13 @@ pub fn Execute(args: CliArgs) -> Result<()> {
             limit,
             within,
             maxDepth,
+            visited,
+            portable,
+            hosts,
+            orderBy,
+            columns,
+            layout,
+            format,
         } => {
             let options = SearchOptions {
                 query,
@@ -177,14 +331,37 @@ pub fn Execute(args: CliArgs) -> Result<()> {
                 limit,
                 within,
                 maxDepth,
+                allHosts: hosts,
+                orderBy,
             };
 
+            if visited {
+                let results = store.SearchVisited(&options);
+
+                if outputJson {
+                    output::PrintVisitedJson(&results)?;
+                } else {
+                    output::PrintVisited(&results, &options.query);
+                }
+
+                return Ok(());
+            }
+
             let results = store.Search(&options);
 
             if outputJson {
                 output::PrintSearchJson(&results)?;
+            } else if let Some(format) = format.as_deref() {
+                output::PrintSearchTemplate(&results, format, layout, portable);
+            } else if layout.is_some() || !columns.is_empty() {
+                output::PrintSearchColumns(
+                    &results,
+                    &columns,
+                    layout.unwrap_or(ListOutput::Table),
+                    portable,
+                );
             } else {
-                output::PrintSearchResults(&results, &options.query);
+                output::PrintSearchResults(&results, &options.query, portable, hosts);
             }
         }
         Action::Add {
@@ -193,34 +370,291 @@ pub fn Execute(args: CliArgs) -> Result<()> {
             expire,
         } => {
             let outcome = store.AddShortcut(&keyword, &path, expire, &addBehavior)?;
-            let resolved = store.ResolveJump(&keyword)?;
-            output::PrintAddOutcome(&keyword, &resolved.targetPath, expire, &outcome);
+            // Templates may not expand until jump time (e.g. an unset variable).
+            let resolved = store
+                .ResolveJump(&keyword)
+                .map(|resolved| resolved.targetPath)
+                .unwrap_or(path);
+
+            if asJson {
+                output::PrintAddOutcomeJson("add", &keyword, &resolved, expire, &outcome)?;
+            } else {
+                output::PrintAddOutcome(&keyword, &resolved, expire, &outcome);
+            }
         }
         Action::AddBulk { pattern } => {
             let added = store.AddBulk(&pattern, &addBehavior)?;
-            output::PrintBulkAdded(&added);
+
+            if asJson {
+                let added: Vec<_> = added
+                    .iter()
+                    .map(|keyword| json!({ "keyword": keyword, "path": store.LookupEntry(keyword).map(|entry| &entry.path) }))
+                    .collect();
+
+                output::PrintResultJson("bulk-add", json!({ "added": added }))?;
+            } else {
+                output::PrintBulkAdded(&added);
+            }
         }
         Action::Copy { existing, newValue } => {
             store.CopyShortcut(&existing, &newValue, &addBehavior)?;
-            output::PrintCopy(&existing, &newValue);
+
+            if asJson {
+                output::PrintResultJson("copy", json!({ "source": existing, "target": newValue }))?;
+            } else {
+                output::PrintCopy(&existing, &newValue);
+            }
+        }
+        Action::Alias { alias, keyword } => {
+            let outcome = store.AddAlias(&alias, &keyword, &addBehavior)?;
+            let resolved = store.ResolveJump(&alias)?;
+
+            if asJson {
+                output::PrintAddOutcomeJson("alias", &alias, &resolved.targetPath, None, &outcome)?;
+            } else {
+                output::PrintAddOutcome(&alias, &resolved.targetPath, None, &outcome);
+            }
+        }
+        Action::Rename {
+            existing,
+            newKeyword,
+        } => {
+            store.RenameShortcut(&existing, &newKeyword)?;
+
+            if asJson {
+                output::PrintResultJson(
+                    "rename",
+                    json!({ "keyword": existing, "renamed_to": newKeyword }),
+                )?;
+            } else {
+                output::PrintRenamed(&existing, &newKeyword);
+            }
+        }
+        Action::Anchor {
+            keyword,
+            name,
+            subpath,
+        } => {
+            let primary = store.SetAnchor(&keyword, &name, subpath.as_deref())?;
+            let subpath = store
+                .Anchors(&primary)
+                .into_iter()
+                .find(|(anchor, _)| *anchor == name)
+                .map(|(_, subpath)| subpath);
+
+            if asJson {
+                output::PrintResultJson(
+                    "anchor",
+                    json!({ "keyword": primary, "anchor": name, "subpath": subpath }),
+                )?;
+            } else {
+                output::PrintAnchorSet(&primary, &name, subpath.as_deref());
+            }
+        }
+        Action::Env {
+            keyword,
+            name,
+            value,
+        } => {
+            let primary = store.SetEnv(&keyword, &name, value.as_deref())?;
+
+            if asJson {
+                output::PrintResultJson(
+                    "env",
+                    json!({ "keyword": primary, "name": name, "value": value }),
+                )?;
+            } else {
+                output::PrintEnvSet(&primary, &name, value.as_deref());
+            }
+        }
+        Action::Hook {
+            keyword,
+            kind,
+            command,
+        } => {
+            let primary = store.SetHook(&keyword, kind, command.as_deref())?;
+
+            if asJson {
+                let hook = match kind {
+                    HookKind::Enter => "on_enter",
+                    HookKind::Leave => "on_leave",
+                };
+
+                output::PrintResultJson(
+                    "hook",
+                    json!({ "keyword": primary, "hook": hook, "command": command }),
+                )?;
+            } else {
+                output::PrintHookSet(&primary, kind, command.as_deref());
+            }
+        }
+        Action::Trust { keyword } => {
+            let primary = store.Trust(&keyword)?;
+
+            if asJson {
+                output::PrintResultJson("trust", json!({ "keyword": primary, "trusted": true }))?;
+            } else {
+                output::PrintTrusted(&primary, &store.Directives(&primary));
+            }
+        }
+        Action::Launcher { keyword, name } => {
+            let primary = store.SetLauncher(&keyword, name.as_deref())?;
+
+            if asJson {
+                output::PrintResultJson(
+                    "launcher",
+                    json!({ "keyword": primary, "launcher": name }),
+                )?;
+            } else {
+                output::PrintLauncherSet(&primary, name.as_deref());
+            }
+        }
+        Action::DefaultSubpath { keyword, subpath } => {
+            let primary = store.SetDefaultSubpath(&keyword, subpath.as_deref())?;
+
+            if asJson {
+                output::PrintResultJson(
+                    "default-subpath",
+                    json!({ "keyword": primary, "subpath": store.DefaultSubpath(&primary) }),
+                )?;
+            } else {
+                output::PrintDefaultSubpathSet(&primary, store.DefaultSubpath(&primary));
+            }
         }
         Action::Remove { keyword } => {
+            let removed = store.LookupEntry(&keyword).map(|entry| entry.path.clone());
+
             store.RemoveShortcut(&keyword)?;
-            output::PrintRemoved(&keyword);
+
+            if asJson {
+                output::PrintResultJson("remove", json!({ "keyword": keyword, "path": removed }))?;
+            } else {
+                output::PrintRemoved(&keyword);
+            }
         }
         Action::PrintPath { target } => {
-            let resolved = store.ResolveJump(&target)?;
-            println!("{}", resolved.targetPath.display());
+            let resolved = ResolveTarget(&store, &target)?;
+
+            if asJson {
+                output::PrintResultJson(
+                    "print-path",
+                    json!({
+                        "target": target,
+                        "keyword": resolved.keyword,
+                        "path": resolved.targetPath,
+                        "base_path": resolved.basePath,
+                    }),
+                )?;
+            } else {
+                println!("{}", resolved.targetPath.display());
+            }
+        }
+        Action::History { outputJson } => {
+            let current = env::current_dir().ok();
+
+            let stack = store.HistoryStack(SessionId().as_deref(), current.as_deref());
+
+            if outputJson {
+                output::PrintHistoryJson(&stack)?;
+            } else {
+                output::PrintHistory(&stack);
+            }
+        }
+        Action::Suggest => {
+            SuggestShortcuts(&mut store, &addBehavior, asJson)?;
+        }
+        Action::MigratePaths => {
+            let rewritten = store.MigratePortablePaths()?;
+
+            if asJson {
+                let rewritten: Vec<_> = rewritten
+                    .iter()
+                    .map(|(keyword, stored)| json!({ "keyword": keyword, "stored": stored }))
+                    .collect();
+
+                output::PrintResultJson("migrate-paths", json!({ "rewritten": rewritten }))?;
+            } else {
+                output::PrintMigratedPaths(&rewritten);
+            }
+        }
+        Action::Which { .. } => unreachable!(),
+        Action::ExportShell { shell } => {
+            let previous = env::var(EXPORTED_VAR).unwrap_or_default();
+
+            let lines = export::ShellExports(&store, shell, &previous);
+
+            if asJson {
+                return output::PrintResultJson("export-shell", json!({ "lines": lines }));
+            }
+
+            for line in lines {
+                println!("{line}");
+            }
         }
+        Action::Resolvers { outputJson } => {
+            let resolvers = store.Resolvers();
+
+            if outputJson {
+                output::PrintResolversJson(&resolvers, store.resolverTimeout)?;
+            } else {
+                output::PrintResolvers(&resolvers, store.resolverTimeout);
+            }
+        }
+        Action::Config { key, value } => match (key, value) {
+            (None, _) if asJson => {
+                let settings: serde_json::Map<String, Value> = store
+                    .ConfigSettings()?
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::String(value)))
+                    .collect();
+
+                output::PrintResultJson("config", json!({ "settings": settings }))?;
+            }
+            (None, _) => output::PrintConfig(&store.ConfigSettings()?),
+            (Some(key), None) => {
+                let settings = store.ConfigSettings()?;
+
+                let found = settings.iter().find(|(existing, _)| *existing == key);
+
+                if asJson {
+                    output::PrintResultJson(
+                        "config",
+                        json!({ "key": key, "value": found.map(|(_, value)| value) }),
+                    )?;
+                }
+
+                match found {
+                    Some(_) if asJson => {}
+                    Some((_, value)) => println!("{value}"),
+                    None => bail!(GotoError::NoMatch),
+                }
+            }
+            (Some(key), Some(value)) => {
+                store.SetConfig(&key, Some(&value))?;
+
+                let value = value.trim();
+
+                if asJson {
+                    output::PrintResultJson(
+                        "config",
+                        json!({ "key": key, "value": (!value.is_empty()).then_some(value) }),
+                    )?;
+                } else {
+                    output::PrintConfigSet(&key, value);
+                }
+            }
+        },
         Action::ShowSort => unreachable!(),
         Action::Jump {
             target,
-            runCursor,
-            runCode,
+            launcher,
             create,
         } => {
-            WarnIfWrapperMissing();
-            JumpAndMaybeCreate(&mut store, &target, runCursor, runCode, create)?;
+            if !asJson {
+                WarnIfWrapperMissing();
+            }
+
+            JumpAndMaybeCreate(&mut store, &target, launcher.as_deref(), create, asJson)?;
         }
         Action::Complete { mode, input } => {
             Complete(&store, &mode, &input)?;
@@ -236,6 +670,7 @@ fn DetermineAction(args: &CliArgs) -> Result<Action> {
         return Ok(Action::InstallWrapper {
             rcPath: args.installWrapperRc.clone(),
             force: args.installWrapperForce,
+            track: args.installWrapperTrack,
         });
     }
 
@@ -255,16 +690,52 @@ fn DetermineAction(args: &CliArgs) -> Result<Action> {
         || args.listRequireBoth
         || args.listGlob
         || args.listRegex
-        || args.listJson
         || args.listLimit.is_some()
+        || args.listVisited
+        || args.listPortable
+        || args.listHosts
         || args.listWithin.is_some()
         || args.listHere
-        || args.listMaxDepth.is_some();
+        || args.listMaxDepth.is_some()
+        || args.listOrderBy.is_some()
+        || !args.listColumns.is_empty()
+        || args.listOutput.is_some();
 
     if listFlagsUsed && args.list.is_none() {
-        bail!(
-            "--glob/--regex/--keyword-only/--path-only/--both/--within/--here/--max-depth/--json/--limit require --list."
-        );
+        bail!(GotoError::Usage("--glob/--regex/--keyword-only/--path-only/--both/--within/--here/--max-depth/--limit/--visited/--portable/--hosts/--order-by/--columns/--output require --list.".to_string()));
+    }
+
+    if args.format.is_some() && args.list.is_none() && args.which.is_none() {
+        bail!(GotoError::Usage(
+            "--format requires --list or --which.".to_string()
+        ));
+    }
+
+    let layoutUsed =
+        args.format.is_some() || !args.listColumns.is_empty() || args.listOutput.is_some();
+
+    if layoutUsed && args.list.is_some() && (args.json || args.listVisited) {
+        bail!(GotoError::Usage(
+            "--columns/--output/--format cannot be combined with --json or --visited.".to_string()
+        ));
+    }
+
+    if args.format.is_some()
+        && (!args.listColumns.is_empty()
+            || matches!(
+                args.listOutput,
+                Some(ListOutput::Table | ListOutput::Tsv | ListOutput::Csv)
+            ))
+    {
+        bail!(GotoError::Usage(
+            "--format replaces --columns; only --output nul may accompany it.".to_string()
+        ));
+    }
+
+    if args.listOrderBy.is_some() && args.listVisited {
+        bail!(GotoError::Usage(
+            "--order-by cannot be combined with --visited.".to_string()
+        ));
     }
 
     if args.installWrapper {
@@ -291,6 +762,42 @@ fn DetermineAction(args: &CliArgs) -> Result<Action> {
         actions += 1;
     }
 
+    if args.alias.is_some() {
+        actions += 1;
+    }
+
+    if args.rename.is_some() {
+        actions += 1;
+    }
+
+    if args.anchor.is_some() {
+        actions += 1;
+    }
+
+    if args.defaultSubpath.is_some() {
+        actions += 1;
+    }
+
+    if args.launcher.is_some() {
+        actions += 1;
+    }
+
+    if args.env.is_some() {
+        actions += 1;
+    }
+
+    if args.onEnter.is_some() {
+        actions += 1;
+    }
+
+    if args.onLeave.is_some() {
+        actions += 1;
+    }
+
+    if args.trust.is_some() {
+        actions += 1;
+    }
+
     if args.remove.is_some() {
         actions += 1;
     }
@@ -299,20 +806,66 @@ fn DetermineAction(args: &CliArgs) -> Result<Action> {
         actions += 1;
     }
 
+    if args.history {
+        actions += 1;
+    }
+
+    if args.suggest {
+        actions += 1;
+    }
+
+    if args.migratePaths {
+        actions += 1;
+    }
+
+    if args.listResolvers {
+        actions += 1;
+    }
+
+    if args.exportShell.is_some() {
+        actions += 1;
+    }
+
+    if args.which.is_some() {
+        actions += 1;
+    }
+
+    if args.config.is_some() {
+        actions += 1;
+    }
+
     if actions > 1 {
-        bail!("Please run one primary action at a time.");
+        bail!(GotoError::Usage(
+            "Please run one primary action at a time.".to_string()
+        ));
     }
 
     if args.expire.is_some() && args.add.is_none() {
-        bail!("--expire can only be used with --add.");
+        bail!(GotoError::Usage(
+            "--expire can only be used with --add.".to_string()
+        ));
     }
 
-    if args.cursor && args.code {
-        bail!("--cursor and --code are mutually exclusive; choose one editor.");
+    if [args.cursor, args.code, args.open.is_some()]
+        .iter()
+        .filter(|used| **used)
+        .count()
+        > 1
+    {
+        bail!(GotoError::Usage(
+            "--cursor, --code, and --open are mutually exclusive; choose one launcher.".to_string()
+        ));
     }
 
-    if args.addForce && args.add.is_none() && args.copy.is_none() && args.bulkAdd.is_none() {
-        bail!("--force can only be used with --add, --copy, or --bulk-add.");
+    if args.addForce
+        && args.add.is_none()
+        && args.copy.is_none()
+        && args.bulkAdd.is_none()
+        && args.alias.is_none()
+    {
+        bail!(GotoError::Usage(
+            "--force can only be used with --add, --copy, --bulk-add, or --alias.".to_string()
+        ));
     }
 
     if let Some(addArgs) = args.add.as_ref() {
@@ -338,6 +891,69 @@ fn DetermineAction(args: &CliArgs) -> Result<Action> {
         });
     }
 
+    if let Some(aliasArgs) = args.alias.as_ref() {
+        return Ok(Action::Alias {
+            alias: aliasArgs[0].clone(),
+            keyword: aliasArgs[1].clone(),
+        });
+    }
+
+    if let Some(renameArgs) = args.rename.as_ref() {
+        return Ok(Action::Rename {
+            existing: renameArgs[0].clone(),
+            newKeyword: renameArgs[1].clone(),
+        });
+    }
+
+    if let Some(anchorArgs) = args.anchor.as_ref() {
+        return Ok(Action::Anchor {
+            keyword: anchorArgs[0].clone(),
+            name: anchorArgs[1].clone(),
+            subpath: anchorArgs.get(2).cloned(),
+        });
+    }
+
+    if let Some(envArgs) = args.env.as_ref() {
+        return Ok(Action::Env {
+            keyword: envArgs[0].clone(),
+            name: envArgs[1].clone(),
+            value: envArgs.get(2).cloned(),
+        });
+    }
+
+    for (hookArgs, kind) in [
+        (args.onEnter.as_ref(), HookKind::Enter),
+        (args.onLeave.as_ref(), HookKind::Leave),
+    ] {
+        if let Some(hookArgs) = hookArgs {
+            return Ok(Action::Hook {
+                keyword: hookArgs[0].clone(),
+                kind,
+                command: hookArgs.get(1).cloned(),
+            });
+        }
+    }
+
+    if let Some(keyword) = args.trust.as_ref() {
+        return Ok(Action::Trust {
+            keyword: keyword.to_string(),
+        });
+    }
+
+    if let Some(launcherArgs) = args.launcher.as_ref() {
+        return Ok(Action::Launcher {
+            keyword: launcherArgs[0].clone(),
+            name: launcherArgs.get(1).cloned(),
+        });
+    }
+
+    if let Some(defaultArgs) = args.defaultSubpath.as_ref() {
+        return Ok(Action::DefaultSubpath {
+            keyword: defaultArgs[0].clone(),
+            subpath: defaultArgs.get(1).cloned(),
+        });
+    }
+
     if args.showSortMode {
         return Ok(Action::ShowSort);
     }
@@ -352,6 +968,53 @@ fn DetermineAction(args: &CliArgs) -> Result<Action> {
         return BuildListAction(args, query);
     }
 
+    if args.suggest {
+        return Ok(Action::Suggest);
+    }
+
+    if args.migratePaths {
+        return Ok(Action::MigratePaths);
+    }
+
+    if let Some(shell) = args.exportShell {
+        return Ok(Action::ExportShell { shell });
+    }
+
+    if let Some(path) = args.which.as_ref() {
+        let path = match path.as_str() {
+            "" => env::current_dir().with_context(|| "Failed to resolve current directory")?,
+            path => {
+                std::path::absolute(path).with_context(|| format!("Failed to resolve '{path}'"))?
+            }
+        };
+
+        return Ok(Action::Which {
+            path,
+            all: args.whichAll,
+            format: args.format.clone(),
+            outputJson: args.json,
+        });
+    }
+
+    if let Some(configArgs) = args.config.as_ref() {
+        return Ok(Action::Config {
+            key: configArgs.first().cloned(),
+            value: configArgs.get(1).cloned(),
+        });
+    }
+
+    if args.listResolvers {
+        return Ok(Action::Resolvers {
+            outputJson: args.json,
+        });
+    }
+
+    if args.history {
+        return Ok(Action::History {
+            outputJson: args.json,
+        });
+    }
+
     if args.printPath {
         let target = args
             .target
@@ -372,15 +1035,22 @@ fn DetermineAction(args: &CliArgs) -> Result<Action> {
 
     Ok(Action::Jump {
         target,
-        runCursor: args.cursor,
-        runCode: args.code,
+        launcher: if args.cursor {
+            Some("cursor".to_string())
+        } else if args.code {
+            Some("code".to_string())
+        } else {
+            args.open.clone()
+        },
         create: !args.noCreate,
     })
 }
 
 fn BuildListAction(args: &CliArgs, query: &str) -> Result<Action> {
     if query.is_empty() && (args.listGlob || args.listRegex) {
-        bail!("Provide a query when using --glob or --regex with --list.");
+        bail!(GotoError::Usage(
+            "Provide a query when using --glob or --regex with --list.".to_string()
+        ));
     }
 
     let scopeRoot = if let Some(path) = args.listWithin.as_ref() {
@@ -401,7 +1071,9 @@ fn BuildListAction(args: &CliArgs, query: &str) -> Result<Action> {
     };
 
     if args.listMaxDepth.is_some() && scopeRoot.is_none() {
-        bail!("--max-depth requires --within or --here.");
+        bail!(GotoError::Usage(
+            "--max-depth requires --within or --here.".to_string()
+        ));
     }
 
     let mode = if args.listGlob {
@@ -422,16 +1094,30 @@ fn BuildListAction(args: &CliArgs, query: &str) -> Result<Action> {
         matchPath: args.listPathOnly,
         requireBoth: args.listRequireBoth,
         mode,
-        outputJson: args.listJson,
+        outputJson: args.json,
         limit: args.listLimit,
         within: scopeRoot,
         maxDepth: args.listMaxDepth,
+        visited: args.listVisited,
+        portable: args.listPortable,
+        hosts: args.listHosts,
+        columns: args.listColumns.clone(),
+        layout: args.listOutput,
+        format: args.format.clone(),
+        orderBy: args
+            .listOrderBy
+            .as_deref()
+            .map(ParseOrderBy)
+            .transpose()?
+            .unwrap_or_default(),
     })
 }
 
 fn ParseAddArgs(values: &[String]) -> Result<(String, PathBuf)> {
     if values.is_empty() {
-        bail!("Usage: goto --add <keyword> <path>");
+        bail!(GotoError::Usage(
+            "Usage: goto --add <keyword> <path>".to_string()
+        ));
     }
 
     if values.len() == 1 {
@@ -449,7 +1135,7 @@ fn ParseAddArgs(values: &[String]) -> Result<(String, PathBuf)> {
     Ok((keyword, path))
 }
 
-fn DeriveKeywordFromPath(path: &PathBuf) -> Result<String> {
+fn DeriveKeywordFromPath(path: &Path) -> Result<String> {
     let name = path
         .file_name()
         .and_then(|s| s.to_str())
@@ -458,50 +1144,491 @@ fn DeriveKeywordFromPath(path: &PathBuf) -> Result<String> {
     Ok(name.to_string())
 }
 
+fn TrackVisit(paths: ConfigPaths, path: &str) -> Result<()> {
+    let Ok(canonical) = Path::new(path).canonicalize() else {
+        return Ok(());
+    };
+
+    if !canonical.is_dir() {
+        return Ok(());
+    }
+
+    store::AppendVisit(
+        &FileStorage,
+        &paths.visitFile,
+        &canonical,
+        SystemClock.Now(),
+    )?;
+
+    Ok(())
+}
+
+fn WhichShortcut(
+    paths: &ConfigPaths,
+    path: &Path,
+    all: bool,
+    format: Option<&str>,
+    outputJson: bool,
+) -> Result<()> {
+    let mut matches = store::WhichShortcuts(&FileStorage, paths, path, SystemClock.Now())?;
+
+    if !all {
+        matches.truncate(1);
+    }
+
+    if outputJson {
+        output::PrintWhichJson(&matches, all)?;
+    } else {
+        output::PrintWhich(&matches, format);
+    }
+
+    if matches.is_empty() && !outputJson {
+        bail!(GotoError::NoMatch);
+    }
+
+    Ok(())
+}
+
+const MAX_SHORTCUT_SUGGESTIONS: usize = 10;
+
+fn SuggestShortcuts(store: &mut Store, behavior: &AddBehavior, asJson: bool) -> Result<()> {
+    store.CompactVisits()?;
+
+    let candidates: Vec<_> = store
+        .FrequentUnsavedDirectories()
+        .into_iter()
+        .take(MAX_SHORTCUT_SUGGESTIONS)
+        .collect();
+
+    let mut taken: Vec<String> = Vec::new();
+
+    let mut suggestions = Vec::new();
+
+    for candidate in candidates {
+        let Ok(keyword) = SuggestKeyword(store, &taken, &candidate.path) else {
+            continue;
+        };
+
+        taken.push(keyword.clone());
+
+        suggestions.push((keyword, candidate));
+    }
+
+    // JSON never prompts; with GOTO_ASSUME_YES=1 every suggestion is added.
+    if asJson {
+        let mut added = Vec::new();
+
+        if behavior.assumeYes {
+            for (keyword, candidate) in &suggestions {
+                let outcome = store.AddShortcut(keyword, &candidate.path, None, behavior)?;
+
+                added.push(output::AddOutcomeJson(
+                    keyword,
+                    &candidate.path,
+                    None,
+                    &outcome,
+                ));
+            }
+        }
+
+        let suggested: Vec<_> = suggestions
+            .iter()
+            .map(|(keyword, candidate)| {
+                json!({
+                    "keyword": keyword,
+                    "path": candidate.path,
+                    "visits": candidate.visit.count,
+                })
+            })
+            .collect();
+
+        return output::PrintResultJson(
+            "suggest",
+            json!({ "suggestions": suggested, "added": added }),
+        );
+    }
+
+    output::PrintShortcutSuggestions(&suggestions);
+
+    if suggestions.is_empty() {
+        return Ok(());
+    }
+
+    let interactive = std::io::stdin().is_terminal();
+
+    if !behavior.assumeYes && !interactive {
+        return Ok(());
+    }
+
+    for (keyword, candidate) in suggestions {
+        let answer = if behavior.assumeYes {
+            SuggestionAnswer::Yes
+        } else {
+            PromptSuggestion(&keyword, &candidate.path)?
+        };
+
+        match answer {
+            SuggestionAnswer::Yes => {
+                let outcome = store.AddShortcut(&keyword, &candidate.path, None, behavior)?;
+                output::PrintAddOutcome(&keyword, &candidate.path, None, &outcome);
+            }
+            SuggestionAnswer::No => continue,
+            SuggestionAnswer::Quit => break,
+        }
+    }
+
+    Ok(())
+}
+
+fn SuggestKeyword(store: &Store, taken: &[String], path: &Path) -> Result<String> {
+    let base = DeriveKeywordFromPath(path)?;
+
+    let available = |keyword: &str| {
+        !taken.iter().any(|t| t == keyword) && !store.entries.iter().any(|e| e.keyword == keyword)
+    };
+
+    if available(&base) {
+        return Ok(base);
+    }
+
+    if let Some(parent) = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|s| s.to_str())
+    {
+        let qualified = format!("{parent}-{base}");
+
+        if available(&qualified) {
+            return Ok(qualified);
+        }
+    }
+
+    let mut counter = 2;
+
+    loop {
+        let numbered = format!("{base}{counter}");
+
+        if available(&numbered) {
+            return Ok(numbered);
+        }
+
+        counter += 1;
+    }
+}
+
+enum SuggestionAnswer {
+    Yes,
+    No,
+    Quit,
+}
+
+fn PromptSuggestion(keyword: &str, path: &Path) -> Result<SuggestionAnswer> {
+    print!("Add '{}' → {}? [y/N/q]: ", keyword, path.display());
+
+    std::io::stdout().flush()?;
+
+    let mut input = String::new();
+
+    std::io::stdin().read_line(&mut input)?;
+
+    let answer = match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => SuggestionAnswer::Yes,
+        "q" | "quit" => SuggestionAnswer::Quit,
+        _ => SuggestionAnswer::No,
+    };
+
+    Ok(answer)
+}
+
 fn JumpAndMaybeCreate(
     store: &mut Store,
     target: &str,
-    runCursor: bool,
-    runCode: bool,
+    launcher: Option<&str>,
     create: bool,
+    asJson: bool,
 ) -> Result<()> {
-    let resolved = store.ResolveJump(target)?;
+    let resolved = ResolveTarget(store, target)?;
+
+    let report = |kind: &str, created: bool| -> Result<()> {
+        output::PrintResultJson(
+            "jump",
+            json!({
+                "target": target,
+                "keyword": resolved.keyword,
+                "path": resolved.targetPath,
+                "base_path": resolved.basePath,
+                "kind": kind,
+                "created": created,
+            }),
+        )
+    };
+
+    let previous = env::current_dir().ok();
+
+    if resolved.targetPath.is_file() {
+        if let Some(parent) = resolved.targetPath.parent() {
+            std::env::set_current_dir(parent)?;
+        }
+
+        if asJson {
+            report("file", false)?;
+        } else {
+            output::PrintOpenFile(&resolved.targetPath);
+        }
+
+        RecordUsage(store, &resolved, previous.as_deref())?;
+        EmitDirectives(store, &resolved)?;
+        WriteDestination(&resolved)?;
+
+        match launcher {
+            Some(requested) => RunLauncher(store, &resolved, requested)?,
+            None => OpenInEditor(&resolved.targetPath)?,
+        }
+
+        return Ok(());
+    }
 
     if resolved.targetPath.exists() {
         std::env::set_current_dir(&resolved.targetPath)?;
-        output::PrintJump(&resolved.targetPath);
-        store.UpdateRecentUsage(&resolved.keyword)?;
-        MaybeRunEditor(&resolved.targetPath, runCursor, runCode)?;
+        if asJson {
+            report("directory", false)?;
+        } else {
+            output::PrintJump(&resolved.targetPath);
+        }
+
+        RecordUsage(store, &resolved, previous.as_deref())?;
+        EmitDirectives(store, &resolved)?;
+        WriteDestination(&resolved)?;
+        MaybeRunLauncher(store, &resolved, launcher)?;
         return Ok(());
     }
 
-    if create {
+    if create || resolved.create {
         std::fs::create_dir_all(&resolved.targetPath)?;
         std::env::set_current_dir(&resolved.targetPath)?;
-        output::PrintCreatedAndJumped(&resolved.targetPath);
-        store.UpdateRecentUsage(&resolved.keyword)?;
-        MaybeRunEditor(&resolved.targetPath, runCursor, runCode)?;
+        if asJson {
+            report("directory", true)?;
+        } else {
+            output::PrintCreatedAndJumped(&resolved.targetPath);
+        }
+
+        RecordUsage(store, &resolved, previous.as_deref())?;
+        EmitDirectives(store, &resolved)?;
+        WriteDestination(&resolved)?;
+        MaybeRunLauncher(store, &resolved, launcher)?;
         return Ok(());
     }
 
-    bail!(
-        "Error: Resolved path '{}' does not exist.",
-        resolved.targetPath.display()
+    bail!(GotoError::PathNotFound {
+        path: resolved.targetPath.clone(),
+        message: format!(
+            "Resolved path '{}' does not exist.",
+            resolved.targetPath.display()
+        )
+    });
+}
+
+fn RecordUsage(store: &mut Store, resolved: &ResolvedJump, previous: Option<&Path>) -> Result<()> {
+    match resolved.keyword.as_deref() {
+        Some(keyword) => store.UpdateRecentUsage(keyword)?,
+        None => store.RecordVisit(&resolved.targetPath)?,
+    }
+
+    if let Some(previous) = previous {
+        if previous != resolved.targetPath {
+            store.PushHistory(SessionId().as_deref(), previous)?;
+        }
+    }
+
+    store.FireHook(
+        HookEvent::Jump,
+        json!({
+            "keyword": resolved.keyword,
+            "path": resolved.targetPath,
+            "base_path": resolved.basePath,
+            "previous": previous,
+        }),
     );
+
+    Ok(())
 }
 
-fn MaybeRunEditor(path: &PathBuf, runCursor: bool, runCode: bool) -> Result<()> {
-    if !runCursor && !runCode {
+// Writes eval-able shell lines to $GOTO_DIRECTIVE_FILE for the wrapper to evaluate
+// after its cd: leave the previously entered shortcut, then enter the new one.
+fn EmitDirectives(store: &Store, resolved: &ResolvedJump) -> Result<()> {
+    let Ok(directiveFile) = env::var("GOTO_DIRECTIVE_FILE") else {
+        return Ok(());
+    };
+
+    if directiveFile.is_empty() {
         return Ok(());
     }
 
-    let (cmd, label) = if runCursor {
-        ("cursor", "cursor")
-    } else {
-        ("code", "code")
+    let active = env::var("GOTO_ACTIVE_SHORTCUT")
+        .ok()
+        .filter(|keyword| !keyword.is_empty());
+
+    if active.is_some() && active == resolved.keyword {
+        return Ok(());
+    }
+
+    let mut lines = Vec::new();
+
+    if let Some(active) = active.as_deref() {
+        let leaving = store.Directives(active);
+
+        if let Some(command) = leaving.onLeave.as_ref() {
+            if store.IsTrusted(active) {
+                lines.push(command.clone());
+            }
+        }
+
+        for (name, _) in &leaving.env {
+            lines.push(format!("unset {name}"));
+        }
+
+        lines.push("unset GOTO_ACTIVE_SHORTCUT".to_string());
+    }
+
+    if let Some(keyword) = resolved.keyword.as_deref() {
+        let entering = store.Directives(keyword);
+
+        if !entering.IsEmpty() {
+            if store.IsTrusted(keyword) {
+                for (name, value) in &entering.env {
+                    lines.push(format!("export {name}={}", ShellQuote(value)));
+                }
+
+                lines.push(format!(
+                    "export GOTO_ACTIVE_SHORTCUT={}",
+                    ShellQuote(keyword)
+                ));
+
+                if let Some(command) = entering.onEnter.as_ref() {
+                    lines.push(command.clone());
+                }
+            } else {
+                output::PrintUntrusted(keyword);
+            }
+        }
+    }
+
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let mut contents = lines.join("\n");
+
+    contents.push('\n');
+
+    std::fs::write(&directiveFile, contents)
+        .with_context(|| format!("Failed to write directives to '{directiveFile}'"))?;
+
+    Ok(())
+}
+
+// Tells the wrapper where the jump went via $GOTO_DEST_FILE, so it can cd
+// without resolving the target (and asking resolver plugins) a second time.
+fn WriteDestination(resolved: &ResolvedJump) -> Result<()> {
+    let Ok(destFile) = env::var("GOTO_DEST_FILE") else {
+        return Ok(());
+    };
+
+    if destFile.is_empty() {
+        return Ok(());
+    }
+
+    std::fs::write(
+        &destFile,
+        resolved.targetPath.as_os_str().as_encoded_bytes(),
+    )
+    .with_context(|| format!("Failed to write the destination to '{destFile}'"))?;
+
+    Ok(())
+}
+
+fn ResolveTarget(store: &Store, target: &str) -> Result<ResolvedJump, GotoError> {
+    match HistoryOffset(target) {
+        Some(steps) => {
+            let current = env::current_dir().ok();
+
+            store.ResolveHistory(SessionId().as_deref(), current.as_deref(), steps)
+        }
+        None => store.ResolveJump(target),
+    }
+}
+
+fn HistoryOffset(target: &str) -> Option<usize> {
+    let digits = target.strip_prefix('-')?;
+
+    if digits.is_empty() {
+        return Some(1);
+    }
+
+    digits.parse::<usize>().ok().filter(|steps| *steps > 0)
+}
+
+fn SessionId() -> Option<String> {
+    let session = env::var("GOTO_SESSION").ok()?;
+
+    let session = session.trim().replace([',', '='], "_");
+
+    if session.is_empty() {
+        return None;
+    }
+
+    Some(session)
+}
+
+fn MaybeRunLauncher(store: &Store, resolved: &ResolvedJump, launcher: Option<&str>) -> Result<()> {
+    match launcher {
+        Some(requested) => RunLauncher(store, resolved, requested),
+        None => Ok(()),
+    }
+}
+
+fn RunLauncher(store: &Store, resolved: &ResolvedJump, requested: &str) -> Result<()> {
+    let (label, template) = store.LauncherFor(resolved.keyword.as_deref(), requested)?;
+
+    let path = &resolved.targetPath;
+
+    // Files open directly; directories open as a workspace.
+    let dir = match path.parent().filter(|_| path.is_file()) {
+        Some(parent) => parent,
+        None => path.as_path(),
     };
 
-    let status = Command::new(cmd).arg(".").current_dir(path).status();
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut words = SplitCommandWords(&template)?;
+
+    if words.is_empty() {
+        bail!(GotoError::Usage(format!(
+            "Launcher '{label}' has an empty command."
+        )));
+    }
+
+    if !template.contains("{path}") && !template.contains("{dir}") {
+        words.push("{path}".to_string());
+    }
+
+    let words: Vec<String> = words
+        .iter()
+        .map(|word| {
+            word.replace("{path}", &path.to_string_lossy())
+                .replace("{dir}", &dir.to_string_lossy())
+                .replace("{name}", &name)
+                .replace("{keyword}", resolved.keyword.as_deref().unwrap_or(""))
+        })
+        .collect();
+
+    let status = Command::new(&words[0])
+        .args(&words[1..])
+        .current_dir(dir)
+        .status();
 
     match status {
         Ok(status) if status.success() => Ok(()),
@@ -510,10 +1637,92 @@ fn MaybeRunEditor(path: &PathBuf, runCursor: bool, runCode: bool) -> Result<()>
     }
 }
 
+// Splits a launcher command into words, honoring quotes and backslashes, so
+// placeholders are substituted per argument instead of through a shell.
+fn SplitCommandWords(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+
+    let mut current = String::new();
+
+    let mut inWord = false;
+
+    let mut quote: Option<char> = None;
+
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+
+                inWord = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                inWord = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if inWord {
+                    words.push(std::mem::take(&mut current));
+                    inWord = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                inWord = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        bail!(GotoError::Usage(format!(
+            "Unterminated quote in launcher command '{command}'."
+        )));
+    }
+
+    if inWord {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+fn OpenInEditor(path: &Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.trim().is_empty());
+
+    let Some(editor) = editor else {
+        output::PrintNoEditor(path);
+        return Ok(());
+    };
+
+    // Run through sh so editors configured with arguments (e.g. "code -w") work.
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(path)
+        .status();
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => bail!("{editor} exited with status {}", status),
+        Err(error) => bail!("failed to run {editor}: {error}"),
+    }
+}
+
 fn Complete(store: &Store, mode: &str, input: &str) -> Result<()> {
     match mode {
         "keywords" => {
-            let mut suggestions = store.SortedKeywords();
+            let mut suggestions = store.CompletionKeywords();
+
+            suggestions.extend(AnchorCompletions(store, input));
 
             if !input.is_empty() {
                 suggestions.retain(|k| k.starts_with(input));
@@ -524,16 +1733,21 @@ fn Complete(store: &Store, mode: &str, input: &str) -> Result<()> {
             }
         }
         "targets" => {
-            let trimmed = input;
-
-            if let Some((keyword, remainder)) = trimmed.split_once('/') {
-                if let Some(entry) = store.entries.iter().find(|e| e.keyword == keyword) {
+            if let Some((keyword, remainder)) = input.split_once('/') {
+                let roots = match store.AnchorRoot(keyword).ok().flatten() {
+                    Some((entry, anchorPath)) => Some((entry.path, anchorPath)),
+                    None => store
+                        .LookupEntry(keyword)
+                        .map(|entry| (entry.path.clone(), entry.path.clone())),
+                };
+
+                if let Some((shortcutRoot, baseRoot)) = roots {
                     let (parentPart, prefix) = match remainder.rsplit_once('/') {
                         Some((parent, leaf)) => (Some(parent.to_string()), leaf.to_string()),
                         None => (None, remainder.to_string()),
                     };
 
-                    let mut searchRoot = entry.path.clone();
+                    let mut searchRoot = baseRoot;
 
                     if let Some(parent) = parentPart.clone() {
                         if !parent.is_empty() {
@@ -542,15 +1756,10 @@ fn Complete(store: &Store, mode: &str, input: &str) -> Result<()> {
                     }
 
                     if searchRoot.is_dir() {
-                        for dirEntry in std::fs::read_dir(&searchRoot)? {
-                            let dirEntry = dirEntry?;
-                            let name = dirEntry.file_name();
-                            let name = name.to_string_lossy();
-
-                            if !name.starts_with(&prefix) {
-                                continue;
-                            }
+                        let candidates =
+                            completion::ListSubpaths(&shortcutRoot, &searchRoot, &prefix)?;
 
+                        for candidate in candidates {
                             let mut suggestion = String::new();
                             suggestion.push_str(keyword);
                             suggestion.push('/');
@@ -562,9 +1771,9 @@ fn Complete(store: &Store, mode: &str, input: &str) -> Result<()> {
                                 }
                             }
 
-                            suggestion.push_str(&name);
+                            suggestion.push_str(&candidate.name);
 
-                            if dirEntry.file_type()?.is_dir() {
+                            if candidate.isDir {
                                 suggestion.push('/');
                             }
 
@@ -576,7 +1785,9 @@ fn Complete(store: &Store, mode: &str, input: &str) -> Result<()> {
                 }
             }
 
-            let mut keywords = store.SortedKeywords();
+            let mut keywords = store.CompletionKeywords();
+
+            keywords.extend(AnchorCompletions(store, input));
 
             if !input.is_empty() {
                 keywords.retain(|k| k.starts_with(input));
@@ -586,12 +1797,28 @@ fn Complete(store: &Store, mode: &str, input: &str) -> Result<()> {
                 println!("{keyword}");
             }
         }
-        _ => bail!("Invalid completion mode"),
+        _ => bail!(GotoError::Usage("Invalid completion mode".to_string())),
     }
 
     Ok(())
 }
 
+fn AnchorCompletions(store: &Store, input: &str) -> Vec<String> {
+    let Some((keyword, _)) = input.split_once(':') else {
+        return Vec::new();
+    };
+
+    let Some(entry) = store.LookupEntry(keyword) else {
+        return Vec::new();
+    };
+
+    store
+        .Anchors(&entry.keyword)
+        .into_iter()
+        .map(|(name, _)| format!("{keyword}:{name}"))
+        .collect()
+}
+
 const WRAPPER_START: &str = "# >>> goto init >>>";
 const WRAPPER_END: &str = "# <<< goto init <<<";
 
@@ -623,6 +1850,23 @@ unset GOTO_COMP_DIR
 "#
 }
 
+fn TrackingHookSnippet() -> &'static str {
+    r#"_goto_track() {
+  [ "$PWD" = "${_GOTO_LAST_PWD:-}" ] && return
+  _GOTO_LAST_PWD="$PWD"
+  ( command to --track "$PWD" >/dev/null 2>&1 & )
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _goto_track
+"#
+}
+
+fn WrapperSnippetWithTracking() -> String {
+    let hooked = format!("{}{}", TrackingHookSnippet(), WRAPPER_END);
+
+    WrapperSnippet().replacen(WRAPPER_END, &hooked, 1)
+}
+
 fn WrapperSnippetBody() -> &'static str {
     r#"GOTO_FUNC_PATH="${XDG_CONFIG_HOME:-$HOME/.config}/zsh/plugins/goto/goto.zsh"
 GOTO_COMP_DIR="${XDG_CONFIG_HOME:-$HOME/.config}/zsh/completions"
@@ -731,7 +1975,7 @@ fn ReplaceWrapperBlock(text: &str, snippet: &str) -> String {
     text.to_string()
 }
 
-fn InstallWrapper(path: &Path, force: bool) -> Result<WrapperAction> {
+fn InstallWrapper(path: &Path, force: bool, track: bool) -> Result<WrapperAction> {
     let target = ResolveRcTarget(path);
 
     if let Some(parent) = target.parent() {
@@ -750,7 +1994,12 @@ fn InstallWrapper(path: &Path, force: bool) -> Result<WrapperAction> {
         String::new()
     };
 
-    let snippet = WrapperSnippet();
+    let snippet = if track {
+        WrapperSnippetWithTracking()
+    } else {
+        WrapperSnippet().to_string()
+    };
+    let snippet = snippet.as_str();
     let body = WrapperSnippetBody();
 
     let already_present = content.contains(snippet)
@@ -833,15 +2082,43 @@ _to() {
       '(-a --add)'{-a,--add}'[add shortcut]:keyword:->keywords :path:_files -/' \
       '(-b --bulk-add)'{-b,--bulk-add}'[add shortcuts from pattern]:pattern:_files -/' \
       '(-c --copy)'{-c,--copy}'[copy existing shortcut]:existing keyword:->keywords :new:' \
+      '--alias[add an alias for a keyword]:alias: :keyword:->keywords' \
+      '--rename[rename a keyword or alias]:existing keyword:->keywords :new:' \
+      '--anchor[name a subpath inside a shortcut]:keyword:->keywords :name: :subpath:' \
+      '--open=-[open the target with a configured launcher]::launcher:' \
+      '--launcher[set the launcher --open uses for a keyword]:keyword:->keywords :launcher:' \
+      '--env[export a variable when entering a shortcut]:keyword:->keywords :name: :value:' \
+      '--on-enter[run a command after entering a shortcut]:keyword:->keywords :command:' \
+      '--on-leave[run a command when leaving a shortcut]:keyword:->keywords :command:' \
+      '--trust[approve the env and hooks of a shortcut]:keyword:->keywords' \
+      '--default-subpath[set the subpath plain jumps land in]:keyword:->keywords :subpath:' \
       '(-f --force)'{-f,--force}'[replace existing keyword or duplicate path]' \
       '(-r --rm)'{-r,--rm}'[remove shortcut]:keyword:->keywords' \
       '(-p --print-path)'{-p,--print-path}'[print stored path]:target:->targets' \
-      '(-s --sort)'{-s,--sort}'[set sorting mode]:mode:(added alpha recent)' \
+      '--history[list directory history for this session]' \
+      '(-s --sort)'{-s,--sort}'[sorting mode for this invocation]:mode:(added alpha recent nearby)' \
       '--show-sort[print current sorting mode]' \
+      '--order-by[order list results by keys (prefix - to reverse)]:keys:' \
+      '--config[show or set persisted settings]:key:(sort_order portable_roots default_launcher resolvers resolver_timeout_ms export_prefix export_cdpath):value:' \
       '--completions[generate completions for shell]:shell:(bash zsh fish)' \
       '--install-wrapper[add goto shell wrapper to your rc file]' \
       '--install-wrapper-rc[override rc file used by --install-wrapper]:rc file:_files' \
       '--install-wrapper-force[overwrite existing wrapper when installing]' \
+      '--install-wrapper-track[also install the directory tracking hook]' \
+      '--hosts[show per-host path overrides (with --list)]' \
+      '--host-only[add the mapping for this host only]' \
+      '--local[override a read-only included shortcut]' \
+      '--portable[show stored ~/$ROOT path forms (with --list)]' \
+      '--migrate-paths[rewrite saved paths into portable form]' \
+      '--resolvers[list external resolver plugins]' \
+      '--which[print the shortcut containing a path]::path:_files -/' \
+      '--all[list every containing shortcut (with --which)]' \
+      '--format[output template for --list or --which]:template:' \
+      '--columns[list columns]:columns:_values -s , column keyword path exists last-used uses expires tags' \
+      '--output[list layout]:format:(table tsv csv nul)' \
+      '--export-shell[print named directories and variables for shortcuts]:shell:(zsh bash fish)' \
+      '--suggest[suggest shortcuts for frequently visited directories]' \
+      '--track[record a visit to a directory]:path:_files -/' \
       '--write-default-completions[write completions to the default location]' \
       '--write-completions[alias for write-default-completions]' \
       '--install-completions[alias for write-default-completions]' \
@@ -853,8 +2130,9 @@ _to() {
       '(-w --within)'{-w,--within}'[list: scope to root]:path:_files -/' \
       '(-H --here)'{-H,--here}'[list: scope to current directory]' \
       '(-d --max-depth)'{-d,--max-depth}'[list: limit depth under scoped root]:depth:' \
-      '(-j --json)'{-j,--json}'[list: output list/search as json]' \
+      '(-j --json)'{-j,--json}'[print results and errors as versioned json]' \
       '(-n --limit)'{-n,--limit}'[list: limit list/search results]:N:' \
+      '--visited[list: show frequently visited unsaved directories]' \
       '(-u --cursor)'{-u,--cursor}'[jump: open in Cursor]' \
       '(-C --code)'{-C,--code}'[jump: open in VS Code]' \
       '(-N --no-create)'{-N,--no-create}'[jump: do not create missing directories]' \
@@ -867,10 +2145,10 @@ _to() {
         _message 'list or search query'
         ;;
       keywords)
-        compadd -- $(to --__complete-mode keywords --__complete-input "$words[CURRENT]")
+        compadd -V goto-keywords -- $(to --__complete-mode keywords --__complete-input "$words[CURRENT]")
         ;;
       targets)
-        compadd -- $(to --__complete-mode targets --__complete-input "$words[CURRENT]")
+        compadd -V goto-targets -- $(to --__complete-mode targets --__complete-input "$words[CURRENT]")
         ;;
     esac
 }
@@ -903,7 +2181,9 @@ fn GenerateCompletions(shell: Shell) -> Result<()> {
 
 fn WriteDefaultCompletions(shell: Shell) -> Result<()> {
     if shell != Shell::Zsh {
-        bail!("--write-default-completions is currently supported only for zsh");
+        bail!(GotoError::Usage(
+            "--write-default-completions is currently supported only for zsh".to_string()
+        ));
     }
 
     let configHome = env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| {
@@ -941,10 +2221,7 @@ fn WriteDefaultCompletions(shell: Shell) -> Result<()> {
 }
 
 fn LegacyToDetected() -> Result<bool> {
-    let output = Command::new("zsh")
-        .arg("-lc")
-        .arg("typeset -f to")
-        .output();
+    let output = Command::new("zsh").arg("-lc").arg("typeset -f to").output();
 
     let Ok(out) = output else {
         return Ok(false);
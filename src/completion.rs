@@ -0,0 +1,213 @@
+use anyhow::Result;
+use glob::{MatchOptions, Pattern};
+use natord::compare;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const MAX_SUBPATH_SUGGESTIONS: usize = 200;
+
+const MAX_SCANNED_ENTRIES: usize = 5000;
+
+// Keep completion instant even in huge directories.
+const SCAN_TIME_BUDGET: Duration = Duration::from_millis(150);
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+#[derive(Debug, Clone)]
+pub struct SubpathCandidate {
+    pub name: String,
+    pub isDir: bool,
+}
+
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: Pattern,
+    base: PathBuf,
+    anchored: bool,
+    dirOnly: bool,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+pub fn ListSubpaths(
+    shortcutRoot: &Path,
+    searchRoot: &Path,
+    prefix: &str,
+) -> Result<Vec<SubpathCandidate>> {
+    let started = Instant::now();
+
+    let ignore = IgnoreRules::Collect(shortcutRoot, searchRoot);
+
+    let showHidden = prefix.starts_with('.');
+
+    let mut candidates = Vec::new();
+
+    for (scanned, dirEntry) in fs::read_dir(searchRoot)?.enumerate() {
+        if scanned >= MAX_SCANNED_ENTRIES || started.elapsed() >= SCAN_TIME_BUDGET {
+            break;
+        }
+
+        let Ok(dirEntry) = dirEntry else {
+            continue;
+        };
+
+        let name = dirEntry.file_name().to_string_lossy().to_string();
+
+        if !name.starts_with(prefix) {
+            continue;
+        }
+
+        if name.starts_with('.') && !showHidden {
+            continue;
+        }
+
+        let path = dirEntry.path();
+
+        let isDir = path.is_dir();
+
+        if !isDir {
+            continue;
+        }
+
+        if ignore.IsIgnored(&path, isDir) {
+            continue;
+        }
+
+        candidates.push(SubpathCandidate { name, isDir });
+    }
+
+    candidates.sort_by(|a, b| compare(&a.name, &b.name));
+
+    candidates.truncate(MAX_SUBPATH_SUGGESTIONS);
+
+    Ok(candidates)
+}
+
+impl IgnoreRules {
+    // Rules from the enclosing git repository's root (or the shortcut root
+    // outside a repository) down to the directory being listed.
+    fn Collect(shortcutRoot: &Path, searchRoot: &Path) -> Self {
+        let mut rules = Self::default();
+
+        let mut dirs: Vec<PathBuf> = Vec::new();
+
+        let repoRoot = shortcutRoot
+            .ancestors()
+            .find(|dir| dir.join(".git").exists());
+
+        if let Some(repoRoot) = repoRoot.filter(|root| *root != shortcutRoot) {
+            for dir in shortcutRoot.ancestors().skip(1) {
+                dirs.push(dir.to_path_buf());
+
+                if dir == repoRoot {
+                    break;
+                }
+            }
+
+            dirs.reverse();
+        }
+
+        dirs.push(shortcutRoot.to_path_buf());
+
+        if let Ok(remainder) = searchRoot.strip_prefix(shortcutRoot) {
+            let mut current = shortcutRoot.to_path_buf();
+
+            for component in remainder.components() {
+                current.push(component);
+                dirs.push(current.clone());
+            }
+        }
+
+        for dir in dirs {
+            for fileName in IGNORE_FILES {
+                rules.LoadFile(&dir, &dir.join(fileName));
+            }
+        }
+
+        rules
+    }
+
+    fn LoadFile(&mut self, base: &Path, file: &Path) {
+        let Ok(text) = fs::read_to_string(file) else {
+            return;
+        };
+
+        for line in text.lines() {
+            let line = line.trim_end();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+
+            let (dirOnly, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+
+            let anchored = line.contains('/');
+
+            let line = line.trim_start_matches('/');
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let Ok(pattern) = Pattern::new(line) else {
+                continue;
+            };
+
+            self.rules.push(IgnoreRule {
+                pattern,
+                base: base.to_path_buf(),
+                anchored,
+                dirOnly,
+                negated,
+            });
+        }
+    }
+
+    fn IsIgnored(&self, path: &Path, isDir: bool) -> bool {
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        let mut ignored = false;
+
+        for rule in &self.rules {
+            if rule.dirOnly && !isDir {
+                continue;
+            }
+
+            let Ok(relative) = path.strip_prefix(&rule.base) else {
+                continue;
+            };
+
+            let matched = if rule.anchored {
+                rule.pattern
+                    .matches_with(&relative.to_string_lossy(), options)
+            } else {
+                path.file_name()
+                    .map(|name| rule.pattern.matches_with(&name.to_string_lossy(), options))
+                    .unwrap_or(false)
+            };
+
+            if matched {
+                ignored = !rule.negated;
+            }
+        }
+
+        ignored
+    }
+}
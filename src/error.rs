@@ -0,0 +1,195 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+// Failures scripts and library users can branch on. `Store` methods return
+// them directly; inside the CLI they travel in `anyhow::Error`, where
+// `Classify` (or `downcast_ref::<GotoError>()`) gets them back. `Other` is an
+// unexpected failure (exit code 1 unless it wraps an I/O error).
+//
+// Messages carry no "Error: " prefix; whoever prints them adds it.
+#[derive(Debug)]
+pub enum GotoError {
+    Usage(String),
+    KeywordNotFound {
+        query: String,
+        message: String,
+        suggestions: Vec<String>,
+    },
+    PathNotFound {
+        path: PathBuf,
+        message: String,
+    },
+    KeywordExists {
+        keyword: String,
+        message: String,
+    },
+    ReadOnly {
+        keyword: String,
+        message: String,
+    },
+    AnchorNotFound {
+        keyword: String,
+        anchor: Option<String>,
+        message: String,
+    },
+    HistoryNotFound {
+        steps: usize,
+        available: usize,
+        message: String,
+    },
+    Ambiguous {
+        component: String,
+        candidates: Vec<String>,
+        message: String,
+    },
+    VariableNotSet {
+        variable: String,
+        message: String,
+    },
+    // A lookup that found nothing (`--which` without a match, an unset
+    // `--config KEY`); there is nothing to print, only the exit code.
+    NoMatch,
+    // Refused to run, e.g. a legacy `to` shell function is still defined.
+    Failed(String),
+    Aborted(String),
+    Io(io::Error),
+    Other(anyhow::Error),
+}
+
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_NOT_FOUND: i32 = 3;
+pub const EXIT_PATH_NOT_FOUND: i32 = 4;
+pub const EXIT_EXISTS: i32 = 5;
+pub const EXIT_ABORTED: i32 = 6;
+pub const EXIT_READ_ONLY: i32 = 7;
+pub const EXIT_IO: i32 = 8;
+
+impl GotoError {
+    pub fn ExitCode(&self) -> i32 {
+        match self {
+            GotoError::Usage(_) => EXIT_USAGE,
+            GotoError::KeywordNotFound { .. } => EXIT_NOT_FOUND,
+            GotoError::PathNotFound { .. } => EXIT_PATH_NOT_FOUND,
+            GotoError::KeywordExists { .. } => EXIT_EXISTS,
+            GotoError::Aborted(_) => EXIT_ABORTED,
+            GotoError::ReadOnly { .. } => EXIT_READ_ONLY,
+            GotoError::AnchorNotFound { .. }
+            | GotoError::HistoryNotFound { .. }
+            | GotoError::Ambiguous { .. }
+            | GotoError::VariableNotSet { .. } => EXIT_NOT_FOUND,
+            GotoError::NoMatch | GotoError::Failed(_) => EXIT_FAILURE,
+            GotoError::Io(_) => EXIT_IO,
+            GotoError::Other(error) => Classify(error).0,
+        }
+    }
+
+    // Stable identifier used as `code` in `--json` error objects.
+    pub fn Code(&self) -> &'static str {
+        match self {
+            GotoError::Usage(_) => "usage",
+            GotoError::KeywordNotFound { .. } => "keyword_not_found",
+            GotoError::PathNotFound { .. } => "path_not_found",
+            GotoError::KeywordExists { .. } => "keyword_exists",
+            GotoError::Aborted(_) => "aborted",
+            GotoError::ReadOnly { .. } => "read_only",
+            GotoError::AnchorNotFound { .. } => "anchor_not_found",
+            GotoError::HistoryNotFound { .. } => "history_not_found",
+            GotoError::Ambiguous { .. } => "ambiguous",
+            GotoError::VariableNotSet { .. } => "variable_not_set",
+            GotoError::NoMatch => "no_match",
+            GotoError::Failed(_) => "error",
+            GotoError::Io(_) => "io",
+            GotoError::Other(error) => Classify(error).1,
+        }
+    }
+}
+
+impl fmt::Display for GotoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GotoError::KeywordNotFound {
+                message,
+                suggestions,
+                ..
+            } if !suggestions.is_empty() => {
+                write!(f, "{message} Did you mean: {}?", suggestions.join(", "))
+            }
+            GotoError::KeywordNotFound { message, .. }
+            | GotoError::PathNotFound { message, .. }
+            | GotoError::KeywordExists { message, .. }
+            | GotoError::ReadOnly { message, .. }
+            | GotoError::AnchorNotFound { message, .. }
+            | GotoError::HistoryNotFound { message, .. }
+            | GotoError::Ambiguous { message, .. }
+            | GotoError::VariableNotSet { message, .. }
+            | GotoError::Usage(message)
+            | GotoError::Failed(message)
+            | GotoError::Aborted(message) => write!(f, "{message}"),
+            GotoError::NoMatch => Ok(()),
+            GotoError::Io(error) => write!(f, "{error}"),
+            GotoError::Other(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for GotoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GotoError::Io(error) => Some(error),
+            GotoError::Other(error) => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GotoError {
+    fn from(error: io::Error) -> Self {
+        GotoError::Io(error)
+    }
+}
+
+// Keeps typed errors typed; anything else becomes `Other`.
+impl From<anyhow::Error> for GotoError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<GotoError>() {
+            Ok(typed) => typed,
+            Err(error) => GotoError::Other(error),
+        }
+    }
+}
+
+// A `--json` failure whose error object is already on stdout; `main` only
+// exits with the code.
+#[derive(Debug)]
+pub struct Reported(pub i32);
+
+impl fmt::Display for Reported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "reported as JSON (exit code {})", self.0)
+    }
+}
+
+impl std::error::Error for Reported {}
+
+// The exit code and JSON `code` for any error, including I/O failures that
+// were propagated with `?` rather than wrapped in `GotoError::Io`.
+pub fn Classify(error: &anyhow::Error) -> (i32, &'static str) {
+    for cause in error.chain() {
+        if let Some(typed) = cause.downcast_ref::<GotoError>() {
+            return (typed.ExitCode(), typed.Code());
+        }
+
+        if cause.downcast_ref::<io::Error>().is_some() {
+            return (EXIT_IO, "io");
+        }
+    }
+
+    (EXIT_FAILURE, "error")
+}
+
+// Errors with nothing to tell the user; `main` exits without printing them.
+pub fn IsQuiet(error: &anyhow::Error) -> bool {
+    matches!(error.downcast_ref::<GotoError>(), Some(GotoError::NoMatch))
+}
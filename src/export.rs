@@ -0,0 +1,187 @@
+use crate::cli::ExportShell;
+use crate::store::Store;
+use natord::compare;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+// Tracks what the previous export defined so a refresh can drop stale names.
+pub const EXPORTED_VAR: &str = "GOTO_EXPORTED";
+
+// The user's own CDPATH, saved by the first export so ours is layered on top
+// of it and can be taken back off.
+const CDPATH_BASE_VAR: &str = "GOTO_CDPATH_BASE";
+
+// Variables goto and its wrapper read, plus shell variables a shortcut must
+// never replace. A keyword that maps onto one of these gets no variable.
+const RESERVED_NAMES: &[&str] = &[
+    "GOTO_ACTIVE_SHORTCUT",
+    "GOTO_ASSUME_YES",
+    "GOTO_CDPATH_BASE",
+    "GOTO_COMP_DIR",
+    "GOTO_DEST_FILE",
+    "GOTO_DIRECTIVE_FILE",
+    "GOTO_EVENT",
+    "GOTO_EXPORTED",
+    "GOTO_EXPORT_SHELL",
+    "GOTO_FUNC_PATH",
+    "GOTO_HOST",
+    "GOTO_QUERY",
+    "GOTO_SESSION",
+    "GOTO_SKIP_LEGACY_CHECK",
+    "GOTO_WRAPPER",
+    "CDPATH",
+    "HOME",
+    "IFS",
+    "OLDPWD",
+    "PATH",
+    "PWD",
+    "SHELL",
+];
+
+// Shell lines exposing every shortcut as a zsh named directory (`~proj`), a
+// `<prefix>PROJ` variable and, when enabled, the parents of directory shortcuts
+// appended to the user's CDPATH. `previous` is the last value of $GOTO_EXPORTED.
+pub fn ShellExports(store: &Store, shell: ExportShell, previous: &str) -> Vec<String> {
+    let mut entries: Vec<_> = store
+        .entries
+        .iter()
+        .filter(|entry| entry.path.is_absolute())
+        .collect();
+
+    entries.sort_by(|a, b| compare(&a.keyword, &b.keyword));
+
+    let mut lines = Vec::new();
+
+    let mut exported: Vec<String> = Vec::new();
+
+    let mut cdpath: Vec<&Path> = Vec::new();
+
+    for entry in entries {
+        let path = entry.path.to_string_lossy();
+
+        let isDir = !entry.path.is_file();
+
+        if shell == ExportShell::Zsh && isDir && IsNamedDirectory(&entry.keyword) {
+            let token = format!("~{}", entry.keyword);
+
+            if !exported.contains(&token) {
+                lines.push(format!("hash -d {}={}", entry.keyword, ShellQuote(&path)));
+
+                exported.push(token);
+            }
+        }
+
+        if let Some(name) = VariableName(&store.exportPrefix, &entry.keyword) {
+            if !exported.contains(&name) {
+                lines.push(SetVariable(shell, &name, &path));
+
+                exported.push(name);
+            }
+        }
+
+        if let Some(parent) = entry.path.parent().filter(|_| isDir) {
+            if !cdpath.contains(&parent) {
+                cdpath.push(parent);
+            }
+        }
+    }
+
+    if store.exportCdpath {
+        let dirs: Vec<String> = cdpath
+            .iter()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect();
+
+        lines.extend(match shell {
+            ExportShell::Fish => [
+                format!("set -q {CDPATH_BASE_VAR}; or set -gx {CDPATH_BASE_VAR} $CDPATH"),
+                format!(
+                    "set -gx CDPATH ${CDPATH_BASE_VAR} {}",
+                    dirs.iter()
+                        .map(|dir| FishQuote(dir))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                ),
+            ],
+            _ => [
+                format!(
+                    "[ -n \"${{{CDPATH_BASE_VAR}+x}}\" ] || export {CDPATH_BASE_VAR}=\"${{CDPATH:-}}\""
+                ),
+                format!(
+                    "export CDPATH=\"${{{CDPATH_BASE_VAR}:-.}}\":{}",
+                    ShellQuote(&dirs.join(":"))
+                ),
+            ],
+        });
+
+        exported.push("CDPATH".to_string());
+    }
+
+    let current: BTreeSet<&str> = exported.iter().map(String::as_str).collect();
+
+    let stale: Vec<String> = previous
+        .split_whitespace()
+        .filter(|token| !current.contains(token))
+        .map(|token| match (shell, token.strip_prefix('~')) {
+            (ExportShell::Zsh, Some(name)) => format!("unhash -d {name} 2>/dev/null"),
+            (_, Some(_)) => String::new(),
+            (ExportShell::Fish, None) if token == "CDPATH" => format!(
+                "set -q {CDPATH_BASE_VAR}; and set -gx CDPATH ${CDPATH_BASE_VAR}; set -e {CDPATH_BASE_VAR}"
+            ),
+            (_, None) if token == "CDPATH" => format!(
+                "if [ -n \"${{{CDPATH_BASE_VAR}:-}}\" ]; then export CDPATH=\"${CDPATH_BASE_VAR}\"; else unset CDPATH; fi; unset {CDPATH_BASE_VAR}"
+            ),
+            (ExportShell::Fish, None) => format!("set -e {token}"),
+            (_, None) => format!("unset {token}"),
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    lines.splice(0..0, stale);
+
+    lines.push(SetVariable(shell, EXPORTED_VAR, &exported.join(" ")));
+
+    lines
+}
+
+fn SetVariable(shell: ExportShell, name: &str, value: &str) -> String {
+    match shell {
+        ExportShell::Fish => format!("set -gx {name} {}", FishQuote(value)),
+        _ => format!("export {name}={}", ShellQuote(value)),
+    }
+}
+
+fn VariableName(prefix: &str, keyword: &str) -> Option<String> {
+    let name: String = format!("{prefix}{keyword}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+
+    (valid && !RESERVED_NAMES.contains(&name.as_str())).then_some(name)
+}
+
+fn IsNamedDirectory(keyword: &str) -> bool {
+    !keyword.starts_with('-')
+        && keyword
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+pub fn ShellQuote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn FishQuote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
@@ -0,0 +1,137 @@
+use crate::paths::ConfigPaths;
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const HOOK_TIMEOUT: Duration = Duration::from_millis(5000);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Add,
+    Remove,
+    Jump,
+    Expire,
+}
+
+impl HookEvent {
+    pub fn Name(self) -> &'static str {
+        match self {
+            HookEvent::Add => "add",
+            HookEvent::Remove => "remove",
+            HookEvent::Jump => "jump",
+            HookEvent::Expire => "expire",
+        }
+    }
+}
+
+// What a store does when something hook-worthy happens. Library stores
+// default to `NoHooks`; the CLI runs `ScriptHooks`.
+pub trait HookRunner: Send + Sync {
+    fn Fire(
+        &self,
+        paths: &ConfigPaths,
+        event: HookEvent,
+        details: Value,
+        timestamp: u64,
+    ) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoHooks;
+
+impl HookRunner for NoHooks {
+    fn Fire(&self, _: &ConfigPaths, _: HookEvent, _: Value, _: u64) -> Result<()> {
+        Ok(())
+    }
+}
+
+// The executables in the hooks directory, run by `Fire`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScriptHooks;
+
+impl HookRunner for ScriptHooks {
+    fn Fire(
+        &self,
+        paths: &ConfigPaths,
+        event: HookEvent,
+        details: Value,
+        timestamp: u64,
+    ) -> Result<()> {
+        Fire(paths, event, details, timestamp)
+    }
+}
+
+// Runs `<hooks dir>/on-<event>` with a JSON payload on stdin. Missing or
+// non-executable hooks are skipped; a hook that cannot run, exits non-zero or
+// outlives `HOOK_TIMEOUT` (and is killed) is returned as an error for the
+// caller to report. The hook's output is discarded so it cannot mix with ours.
+pub fn Fire(paths: &ConfigPaths, event: HookEvent, details: Value, timestamp: u64) -> Result<()> {
+    let hookPath = paths.hooksDir.join(format!("on-{}", event.Name()));
+
+    let executable = hookPath
+        .metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false);
+
+    if !executable {
+        return Ok(());
+    }
+
+    let failed = |detail: String| anyhow!("hook '{}' failed: {detail}", hookPath.display());
+
+    let mut payload = json!({
+        "event": event.Name(),
+        "timestamp": timestamp,
+    });
+
+    if let (Some(payload), Value::Object(details)) = (payload.as_object_mut(), details) {
+        payload.extend(details);
+    }
+
+    let mut child = Command::new(&hookPath)
+        .env("GOTO_EVENT", event.Name())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|error| failed(error.to_string()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let written = writeln!(stdin, "{payload}");
+
+        if let Err(error) = written {
+            if error.kind() != ErrorKind::BrokenPipe {
+                let _ = child.wait();
+
+                return Err(failed(error.to_string()));
+            }
+        }
+    }
+
+    let deadline = Instant::now() + HOOK_TIMEOUT;
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(failed(status.to_string())),
+            Ok(None) => {}
+            Err(error) => return Err(failed(error.to_string())),
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+
+            return Err(failed(format!(
+                "timed out after {}ms",
+                HOOK_TIMEOUT.as_millis()
+            )));
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+}
@@ -1,9 +1,17 @@
 #![allow(non_snake_case)]
 
 pub mod cli;
+pub mod clock;
 pub mod commands;
+pub mod completion;
+pub mod error;
+pub mod export;
+pub mod hooks;
 pub mod output;
 pub mod paths;
+pub mod prompt;
+pub mod resolvers;
+pub mod storage;
 pub mod store;
 
 use anyhow::Result;
@@ -11,5 +19,14 @@ use anyhow::Result;
 pub fn Run() -> Result<()> {
     let cli = cli::ParseArgs()?;
 
-    commands::Execute(cli)
+    let jsonErrors = cli.json;
+
+    match commands::Execute(cli) {
+        Err(error) if jsonErrors && !error::IsQuiet(&error) => {
+            output::PrintErrorJson(&error)?;
+
+            Err(error::Reported(error::Classify(&error).0).into())
+        }
+        result => result,
+    }
 }
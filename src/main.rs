@@ -1,6 +1,15 @@
+use goto::error::{self, Reported};
+
 fn main() {
     if let Err(error) = goto::Run() {
-        eprintln!("{error}");
-        std::process::exit(1);
+        if let Some(Reported(code)) = error.downcast_ref::<Reported>() {
+            std::process::exit(*code);
+        }
+
+        if !error::IsQuiet(&error) {
+            eprintln!("Error: {error}");
+        }
+
+        std::process::exit(error::Classify(&error).0);
     }
 }
@@ -1,7 +1,17 @@
-use crate::store::{AddOutcome, SearchResult, Store};
+use crate::cli::{ListColumn, ListOutput};
+use crate::error::{self, GotoError};
+use crate::resolvers::Resolver;
+use crate::store::{
+    AddOutcome, HistoryEntry, HookKind, SearchResult, ShortcutDirectives, ShortcutMatch, Store,
+    VisitedDirectory,
+};
 use anyhow::Result;
-use owo_colors::OwoColorize;
-use std::path::PathBuf;
+use owo_colors::{OwoColorize, Stream};
+use serde_json::Value;
+use std::env;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub fn PrintSavedShortcuts(store: &Store) {
     let sorted = store.SortedKeywords();
@@ -36,7 +46,7 @@ pub fn PrintSavedShortcuts(store: &Store) {
 
     let cols = 3;
 
-    let rows = (shown + cols - 1) / cols;
+    let rows = shown.div_ceil(cols);
 
     for row in 0..rows {
         for col in 0..cols {
@@ -59,10 +69,7 @@ pub fn PrintSavedShortcuts(store: &Store) {
         println!("  … and {} more", total - shown);
     }
 
-    println!(
-        "\nCurrent sorting mode: {}",
-        store.sortMode.clone().ToLabel()
-    );
+    println!("\nCurrent sorting mode: {}", store.sortMode.Value());
 }
 
 pub fn PrintList(store: &Store) {
@@ -86,7 +93,7 @@ pub fn PrintList(store: &Store) {
     }
 }
 
-pub fn PrintSearchResults(results: &[SearchResult], query: &str) {
+pub fn PrintSearchResults(results: &[SearchResult], query: &str, portable: bool, hosts: bool) {
     if results.is_empty() {
         if query.is_empty() {
             println!("{}", "No shortcuts saved.".red().bold());
@@ -103,20 +110,342 @@ pub fn PrintSearchResults(results: &[SearchResult], query: &str) {
     }
 
     for result in results {
-        match result.expiry {
-            Some(ts) => println!(
-                "{} → {} (expires {})",
-                result.keyword.bold().cyan(),
-                result.path.display().to_string().dimmed(),
-                ts
-            ),
-            None => println!(
-                "{} → {}",
-                result.keyword.bold().cyan(),
-                result.path.display().to_string().dimmed()
-            ),
+        let shownPath = if portable {
+            result.portablePath.clone()
+        } else {
+            result.path.display().to_string()
+        };
+
+        let mut line = format!("{} → {}", result.keyword.bold().cyan(), shownPath.dimmed());
+
+        if result.isFile {
+            line.push_str(&format!(" {}", "(file)".yellow()));
+        }
+
+        if let Some(template) = result.template.as_ref().filter(|_| !portable) {
+            line.push_str(&format!(" (template: {template})"));
+        }
+
+        if !result.aliases.is_empty() {
+            line.push_str(&format!(" (aliases: {})", result.aliases.join(", ")));
+        }
+
+        if let Some(ts) = result.expiry {
+            line.push_str(&format!(" (expires {ts})"));
+        }
+
+        if let Some(host) = result.host.as_ref().filter(|_| hosts) {
+            line.push_str(&format!(" [host: {host}]"));
+        }
+
+        if let Some(origin) = result.origin.as_ref() {
+            line.push_str(&format!(
+                " {}",
+                format!("[from {}]", origin.display()).magenta()
+            ));
+        }
+
+        println!("{line}");
+
+        if hosts {
+            for (host, path) in &result.variants {
+                let label = match host {
+                    Some(host) => format!("[host: {host}]"),
+                    None => "[shared]".to_string(),
+                };
+
+                println!("    {} → {}", label.magenta(), path.dimmed());
+            }
+        }
+
+        if let Some(subpath) = result.defaultSubpath.as_ref() {
+            println!("    {} {}", "default →".dimmed(), subpath);
+        }
+
+        PrintDirectives(&result.directives, result.trusted);
+
+        for (name, subpath) in &result.anchors {
+            println!(
+                "    {} → {}",
+                format!("{}:{}", result.keyword, name).cyan(),
+                subpath
+            );
+        }
+    }
+}
+
+const DEFAULT_COLUMNS: [ListColumn; 2] = [ListColumn::Keyword, ListColumn::Path];
+
+const TEMPLATE_COLUMNS: [(&str, ListColumn); 7] = [
+    ("{keyword}", ListColumn::Keyword),
+    ("{path}", ListColumn::Path),
+    ("{exists}", ListColumn::Exists),
+    ("{last-used}", ListColumn::LastUsed),
+    ("{uses}", ListColumn::Uses),
+    ("{expires}", ListColumn::Expires),
+    ("{tags}", ListColumn::Tags),
+];
+
+pub fn PrintSearchColumns(
+    results: &[SearchResult],
+    columns: &[ListColumn],
+    layout: ListOutput,
+    portable: bool,
+) {
+    let columns = if columns.is_empty() {
+        &DEFAULT_COLUMNS[..]
+    } else {
+        columns
+    };
+
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|result| {
+            columns
+                .iter()
+                .map(|column| ColumnValue(result, *column, portable))
+                .collect()
+        })
+        .collect();
+
+    match layout {
+        ListOutput::Table => PrintTable(columns, &rows),
+        ListOutput::Tsv => {
+            for row in rows {
+                let fields: Vec<String> = row
+                    .iter()
+                    .map(|field| field.replace(['\t', '\n'], " "))
+                    .collect();
+
+                println!("{}", fields.join("\t"));
+            }
+        }
+        ListOutput::Csv => {
+            let header: Vec<&str> = columns.iter().map(|column| ColumnName(*column)).collect();
+
+            println!("{}", header.join(","));
+
+            for row in rows {
+                let fields: Vec<String> = row.iter().map(|field| CsvField(field)).collect();
+
+                println!("{}", fields.join(","));
+            }
+        }
+        ListOutput::Nul => {
+            for row in rows {
+                print!("{}\0", row.join("\t"));
+            }
+        }
+    }
+}
+
+pub fn PrintSearchTemplate(
+    results: &[SearchResult],
+    format: &str,
+    layout: Option<ListOutput>,
+    portable: bool,
+) {
+    let template = Unescape(format);
+
+    let terminator = if layout == Some(ListOutput::Nul) {
+        "\0"
+    } else {
+        "\n"
+    };
+
+    for result in results {
+        let mut line = template.clone();
+
+        for (placeholder, column) in TEMPLATE_COLUMNS {
+            if line.contains(placeholder) {
+                line = line.replace(placeholder, &ColumnValue(result, column, portable));
+            }
+        }
+
+        print!("{line}{terminator}");
+    }
+}
+
+fn PrintTable(columns: &[ListColumn], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .map(|column| ColumnName(*column).len())
+        .collect();
+
+    for row in rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count().max(1));
+        }
+    }
+
+    // Shrink the path column (or the widest one) so the table fits the terminal.
+    if let Some(available) = TerminalWidth() {
+        let total = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+
+        let flexible = columns
+            .iter()
+            .position(|column| *column == ListColumn::Path)
+            .or_else(|| (0..widths.len()).max_by_key(|&index| widths[index]));
+
+        if let Some(index) = flexible.filter(|_| total > available) {
+            widths[index] = widths[index]
+                .saturating_sub(total - available)
+                .max(ColumnName(columns[index]).len().max(8));
+        }
+    }
+
+    let header: Vec<String> = columns
+        .iter()
+        .zip(&widths)
+        .map(|(column, width)| format!("{:width$}", ColumnName(*column).to_uppercase()))
+        .collect();
+
+    println!(
+        "{}",
+        header
+            .join("  ")
+            .trim_end()
+            .if_supports_color(Stream::Stdout, |text| text.bold())
+    );
+
+    for row in rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .zip(row)
+            .zip(&widths)
+            .map(|((column, field), width)| {
+                let field = if field.is_empty() { "-" } else { field };
+
+                let cell = format!(
+                    "{:width$}",
+                    Truncate(field, *width, *column == ListColumn::Path)
+                );
+
+                if *column == ListColumn::Keyword {
+                    cell.if_supports_color(Stream::Stdout, |text| text.cyan())
+                        .to_string()
+                } else {
+                    cell
+                }
+            })
+            .collect();
+
+        println!("{}", cells.join("  ").trim_end());
+    }
+}
+
+fn ColumnValue(result: &SearchResult, column: ListColumn, portable: bool) -> String {
+    match column {
+        ListColumn::Keyword => result.keyword.clone(),
+        ListColumn::Path if portable => result.portablePath.clone(),
+        ListColumn::Path => result.path.display().to_string(),
+        ListColumn::Exists => if result.path.exists() { "yes" } else { "no" }.to_string(),
+        ListColumn::LastUsed => result.lastUsed.map(|ts| ts.to_string()).unwrap_or_default(),
+        ListColumn::Uses => result.uses.to_string(),
+        ListColumn::Expires => result.expiry.map(|ts| ts.to_string()).unwrap_or_default(),
+        ListColumn::Tags => ResultTags(result).join(","),
+    }
+}
+
+fn ColumnName(column: ListColumn) -> &'static str {
+    match column {
+        ListColumn::Keyword => "keyword",
+        ListColumn::Path => "path",
+        ListColumn::Exists => "exists",
+        ListColumn::LastUsed => "last-used",
+        ListColumn::Uses => "uses",
+        ListColumn::Expires => "expires",
+        ListColumn::Tags => "tags",
+    }
+}
+
+// The markers the plain listing shows in parentheses, as short labels.
+fn ResultTags(result: &SearchResult) -> Vec<String> {
+    let mut tags = Vec::new();
+
+    if result.isFile {
+        tags.push("file".to_string());
+    }
+
+    if result.template.is_some() {
+        tags.push("template".to_string());
+    }
+
+    if let Some(host) = result.host.as_ref() {
+        tags.push(format!("host:{host}"));
+    }
+
+    if result.origin.is_some() {
+        tags.push("included".to_string());
+    }
+
+    tags.extend(result.aliases.iter().map(|alias| format!("alias:{alias}")));
+
+    tags
+}
+
+fn Truncate(value: &str, width: usize, keepTail: bool) -> String {
+    let count = value.chars().count();
+
+    if count <= width {
+        return value.to_string();
+    }
+
+    let kept = width.saturating_sub(1);
+
+    if keepTail {
+        format!("…{}", value.chars().skip(count - kept).collect::<String>())
+    } else {
+        format!("{}…", value.chars().take(kept).collect::<String>())
+    }
+}
+
+fn CsvField(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// `$COLUMNS`, or 80 when printing to a terminal; piped tables are never shrunk.
+fn TerminalWidth() -> Option<usize> {
+    if let Some(columns) = env::var("COLUMNS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+    {
+        return Some(columns);
+    }
+
+    std::io::stdout().is_terminal().then_some(80)
+}
+
+// Expands the `\t`, `\n`, `\0` and `\\` escapes of a --format template.
+fn Unescape(template: &str) -> String {
+    let mut expanded = String::new();
+
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            expanded.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => expanded.push('\t'),
+            Some('n') => expanded.push('\n'),
+            Some('0') => expanded.push('\0'),
+            Some('\\') => expanded.push('\\'),
+            Some(other) => {
+                expanded.push('\\');
+                expanded.push(other);
+            }
+            None => expanded.push('\\'),
         }
     }
+
+    expanded
 }
 
 pub fn PrintSearchJson(results: &[SearchResult]) -> Result<()> {
@@ -127,6 +456,140 @@ pub fn PrintSearchJson(results: &[SearchResult]) -> Result<()> {
                 "keyword": result.keyword,
                 "path": result.path,
                 "expiry": result.expiry,
+                "template": result.template,
+                "portable": result.portablePath,
+                "aliases": result.aliases,
+                "anchors": result
+                    .anchors
+                    .iter()
+                    .cloned()
+                    .collect::<std::collections::BTreeMap<_, _>>(),
+                "default": result.defaultSubpath,
+                "env": result
+                    .directives
+                    .env
+                    .iter()
+                    .cloned()
+                    .collect::<std::collections::BTreeMap<_, _>>(),
+                "on_enter": result.directives.onEnter,
+                "on_leave": result.directives.onLeave,
+                "trusted": result.trusted,
+                "kind": if result.isFile { "file" } else { "directory" },
+                "host": result.host,
+                "origin": result.origin,
+                "uses": result.uses,
+                "last_used": result.lastUsed,
+                "variants": result
+                    .variants
+                    .iter()
+                    .map(|(host, path)| serde_json::json!({ "host": host, "path": path }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+
+    Ok(())
+}
+
+pub fn PrintVisited(results: &[VisitedDirectory], query: &str) {
+    if results.is_empty() {
+        if query.is_empty() {
+            println!("{}", "No unsaved directories visited yet.".red().bold());
+
+            return;
+        }
+
+        println!(
+            "{}",
+            format!("No visited directories matched '{}'.", query)
+                .red()
+                .bold()
+        );
+
+        return;
+    }
+
+    for result in results {
+        println!(
+            "{:>5}  {}",
+            result.visit.count,
+            result.path.display().to_string().dimmed()
+        );
+    }
+
+    println!(
+        "\nPromote one with: {}",
+        "to --add [KEYWORD] PATH".bold().cyan()
+    );
+}
+
+pub fn PrintVisitedJson(results: &[VisitedDirectory]) -> Result<()> {
+    let payload: Vec<_> = results
+        .iter()
+        .map(|result| {
+            serde_json::json!({
+                "path": result.path,
+                "visits": result.visit.count,
+                "lastVisit": result.visit.lastVisit,
+            })
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+
+    Ok(())
+}
+
+pub fn PrintShortcutSuggestions(suggestions: &[(String, VisitedDirectory)]) {
+    if suggestions.is_empty() {
+        println!("{}", "No shortcut suggestions yet.".yellow());
+        return;
+    }
+
+    println!("{}", "Suggested shortcuts:".magenta());
+
+    for (keyword, visited) in suggestions {
+        println!(
+            "  {} → {} ({} visits)",
+            keyword.bold().cyan(),
+            visited.path.display().to_string().dimmed(),
+            visited.visit.count
+        );
+    }
+}
+
+pub fn PrintHistory(stack: &[HistoryEntry]) {
+    if stack.is_empty() {
+        println!("{}", "No directory history yet.".red().bold());
+        return;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    for (idx, entry) in stack.iter().enumerate() {
+        println!(
+            "{:>4}  {:>9}  {}",
+            format!("-{}", idx + 1).bold().cyan(),
+            FormatAge(now.saturating_sub(entry.timestamp)),
+            entry.path.display().to_string().dimmed()
+        );
+    }
+}
+
+pub fn PrintHistoryJson(stack: &[HistoryEntry]) -> Result<()> {
+    let payload: Vec<_> = stack
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            serde_json::json!({
+                "offset": idx + 1,
+                "path": entry.path,
+                "timestamp": entry.timestamp,
             })
         })
         .collect();
@@ -136,7 +599,129 @@ pub fn PrintSearchJson(results: &[SearchResult]) -> Result<()> {
     Ok(())
 }
 
-pub fn PrintAdded(keyword: &str, path: &PathBuf, expire: Option<u64>) {
+fn FormatAge(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+// Bumped whenever a field is renamed or removed; new fields may appear at any time.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+// `{"schema": N, "action": ACTION, ...details}`, the shape of every `--json`
+// result except the list-style outputs (--list, --history, --which, --resolvers).
+pub fn PrintResultJson(action: &str, details: Value) -> Result<()> {
+    let mut payload = serde_json::json!({
+        "schema": JSON_SCHEMA_VERSION,
+        "action": action,
+    });
+
+    if let (Some(payload), Value::Object(details)) = (payload.as_object_mut(), details) {
+        payload.extend(details);
+    }
+
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+
+    Ok(())
+}
+
+pub fn PrintErrorJson(error: &anyhow::Error) -> Result<()> {
+    let (exitCode, code) = error::Classify(error);
+
+    let payload = match error.downcast_ref::<GotoError>() {
+        Some(GotoError::KeywordNotFound {
+            query,
+            message,
+            suggestions,
+        }) => serde_json::json!({
+            "schema": JSON_SCHEMA_VERSION,
+            "error": message,
+            "code": code,
+            "exit_code": exitCode,
+            "query": query,
+            "suggestions": suggestions,
+        }),
+        _ => serde_json::json!({
+            "schema": JSON_SCHEMA_VERSION,
+            "error": error.to_string(),
+            "code": code,
+            "exit_code": exitCode,
+            "suggestions": [],
+        }),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+
+    Ok(())
+}
+
+pub fn AddOutcomeJson(
+    keyword: &str,
+    resolvedPath: &Path,
+    expire: Option<u64>,
+    outcome: &AddOutcome,
+) -> Value {
+    let mut payload = match outcome {
+        AddOutcome::Added {
+            path,
+            expiry,
+            duplicateKeywords,
+        } => serde_json::json!({
+            "outcome": "added",
+            "path": path,
+            "expires": expire.or(*expiry),
+            "also_saved_as": duplicateKeywords,
+        }),
+        AddOutcome::AlreadyPresent {
+            path,
+            expiry,
+            expiryChanged,
+        } => serde_json::json!({
+            "outcome": "already_present",
+            "path": path,
+            "expires": expire.or(*expiry),
+            "expiry_changed": expiryChanged,
+        }),
+        AddOutcome::Replaced {
+            previousPath,
+            newPath,
+            expiry,
+        } => serde_json::json!({
+            "outcome": "replaced",
+            "path": newPath,
+            "previous_path": previousPath,
+            "expires": expire.or(*expiry),
+        }),
+        AddOutcome::Aliased { target, path } => serde_json::json!({
+            "outcome": "aliased",
+            "path": path,
+            "alias_of": target,
+        }),
+    };
+
+    payload["keyword"] = Value::from(keyword);
+    payload["resolved_path"] = serde_json::json!(resolvedPath);
+
+    payload
+}
+
+pub fn PrintAddOutcomeJson(
+    action: &str,
+    keyword: &str,
+    resolvedPath: &Path,
+    expire: Option<u64>,
+    outcome: &AddOutcome,
+) -> Result<()> {
+    PrintResultJson(
+        action,
+        AddOutcomeJson(keyword, resolvedPath, expire, outcome),
+    )
+}
+
+pub fn PrintAdded(keyword: &str, path: &Path, expire: Option<u64>) {
     match expire {
         Some(ts) => println!(
             "{} {} → {} (expires {})",
@@ -154,12 +739,7 @@ pub fn PrintAdded(keyword: &str, path: &PathBuf, expire: Option<u64>) {
     }
 }
 
-pub fn PrintAlreadyPresent(
-    keyword: &str,
-    path: &PathBuf,
-    expire: Option<u64>,
-    expiryChanged: bool,
-) {
+pub fn PrintAlreadyPresent(keyword: &str, path: &Path, expire: Option<u64>, expiryChanged: bool) {
     let base = format!(
         "Keyword '{}' already points to {}",
         keyword.bold().cyan(),
@@ -176,7 +756,7 @@ pub fn PrintAlreadyPresent(
     }
 }
 
-pub fn PrintReplaced(keyword: &str, previous: &PathBuf, newPath: &PathBuf, expire: Option<u64>) {
+pub fn PrintReplaced(keyword: &str, previous: &Path, newPath: &Path, expire: Option<u64>) {
     match expire {
         Some(ts) => println!(
             "{} {}: {} → {} (expires {})",
@@ -211,7 +791,7 @@ pub fn PrintDuplicateNote(keywords: &[String]) {
 
 pub fn PrintAddOutcome(
     keyword: &str,
-    resolvedPath: &PathBuf,
+    resolvedPath: &Path,
     expire: Option<u64>,
     outcome: &AddOutcome,
 ) {
@@ -238,6 +818,9 @@ pub fn PrintAddOutcome(
         } => {
             PrintReplaced(keyword, previousPath, newPath, expire.or(*expiry));
         }
+        AddOutcome::Aliased { target, path } => {
+            PrintAliased(keyword, target, path);
+        }
     }
 
     // Provide resolved target for caller visibility when it differs by canonicalization.
@@ -246,6 +829,7 @@ pub fn PrintAddOutcome(
             AddOutcome::Added { path, .. } => path,
             AddOutcome::AlreadyPresent { path, .. } => path,
             AddOutcome::Replaced { newPath, .. } => newPath,
+            AddOutcome::Aliased { path, .. } => path,
         }
     {
         println!(
@@ -255,6 +839,293 @@ pub fn PrintAddOutcome(
     }
 }
 
+pub fn PrintAliased(alias: &str, target: &str, path: &Path) {
+    println!(
+        "{} {} {} {} ({})",
+        "Aliased".green(),
+        alias.bold().cyan(),
+        "->".dimmed(),
+        target.bold().cyan(),
+        path.display().to_string().blue()
+    );
+}
+
+pub fn PrintRenamed(existing: &str, newKeyword: &str) {
+    println!(
+        "{} {} {} {}",
+        "Renamed".green(),
+        existing.bold().cyan(),
+        "->".dimmed(),
+        newKeyword.bold().cyan()
+    );
+}
+
+pub fn PrintAnchorSet(keyword: &str, name: &str, subpath: Option<&str>) {
+    let label = format!("{keyword}:{name}");
+
+    match subpath {
+        Some(subpath) => println!(
+            "{} {} {} {}",
+            "Anchored".green(),
+            label.bold().cyan(),
+            "→".dimmed(),
+            subpath
+        ),
+        None => println!("{} {}", "Removed anchor".yellow(), label.bold().cyan()),
+    }
+}
+
+fn PrintDirectives(directives: &ShortcutDirectives, trusted: bool) {
+    if directives.IsEmpty() {
+        return;
+    }
+
+    for (name, value) in &directives.env {
+        println!("    {} {name}={value}", "env".dimmed());
+    }
+
+    if let Some(command) = directives.onEnter.as_ref() {
+        println!("    {} {command}", "on enter:".dimmed());
+    }
+
+    if let Some(command) = directives.onLeave.as_ref() {
+        println!("    {} {command}", "on leave:".dimmed());
+    }
+
+    if !trusted {
+        println!("    {}", "(untrusted; review and run `to --trust`)".red());
+    }
+}
+
+pub fn PrintEnvSet(keyword: &str, name: &str, value: Option<&str>) {
+    match value {
+        Some(value) => println!(
+            "{} {} {} {name}={value}",
+            "Env for".green(),
+            keyword.bold().cyan(),
+            "→".dimmed()
+        ),
+        None => println!(
+            "{} {name} {} {}",
+            "Removed env".yellow(),
+            "from".dimmed(),
+            keyword.bold().cyan()
+        ),
+    }
+}
+
+pub fn PrintHookSet(keyword: &str, kind: HookKind, command: Option<&str>) {
+    let label = match kind {
+        HookKind::Enter => "On-enter",
+        HookKind::Leave => "On-leave",
+    };
+
+    match command {
+        Some(command) => println!(
+            "{} {} {} {}",
+            format!("{label} for").green(),
+            keyword.bold().cyan(),
+            "→".dimmed(),
+            command
+        ),
+        None => println!(
+            "{} {}",
+            format!("Cleared {} for", label.to_lowercase()).yellow(),
+            keyword.bold().cyan()
+        ),
+    }
+}
+
+pub fn PrintTrusted(keyword: &str, directives: &ShortcutDirectives) {
+    if directives.IsEmpty() {
+        println!(
+            "{} {} {}",
+            "Nothing to trust:".yellow(),
+            keyword.bold().cyan(),
+            "has no env or hooks.".yellow()
+        );
+        return;
+    }
+
+    println!("{} {}", "Trusted".green(), keyword.bold().cyan());
+
+    PrintDirectives(directives, true);
+}
+
+pub fn PrintUntrusted(keyword: &str) {
+    eprintln!(
+        "{}",
+        format!(
+            "Skipping env and hooks for '{keyword}': they changed since you last approved them. Review with `to --list {keyword}` and run `to --trust {keyword}`."
+        )
+        .yellow()
+    );
+}
+
+pub fn PrintLauncherSet(keyword: &str, name: Option<&str>) {
+    match name {
+        Some(name) => println!(
+            "{} {} {} {}",
+            "Launcher for".green(),
+            keyword.bold().cyan(),
+            "→".dimmed(),
+            name
+        ),
+        None => println!(
+            "{} {}",
+            "Cleared launcher for".yellow(),
+            keyword.bold().cyan()
+        ),
+    }
+}
+
+pub fn PrintDefaultSubpathSet(keyword: &str, subpath: Option<&str>) {
+    match subpath {
+        Some(subpath) => println!(
+            "{} {} {} {}",
+            "Default for".green(),
+            keyword.bold().cyan(),
+            "→".dimmed(),
+            subpath
+        ),
+        None => println!(
+            "{} {}",
+            "Cleared default subpath for".yellow(),
+            keyword.bold().cyan()
+        ),
+    }
+}
+
+pub fn PrintMigratedPaths(rewritten: &[(String, String)]) {
+    if rewritten.is_empty() {
+        println!("{}", "All saved paths are already portable.".green());
+        return;
+    }
+
+    for (keyword, form) in rewritten {
+        println!(
+            "{} {} {} {}",
+            "Rewrote".green(),
+            keyword.bold().cyan(),
+            "→".dimmed(),
+            form
+        );
+    }
+}
+
+pub fn PrintWhich(matches: &[ShortcutMatch], format: Option<&str>) {
+    for found in matches {
+        let target = WhichTarget(found);
+
+        match format {
+            Some(format) => println!(
+                "{}",
+                Unescape(format)
+                    .replace("{keyword}", &found.keyword)
+                    .replace("{remainder}", &found.remainder)
+                    .replace("{path}", &found.path.to_string_lossy())
+                    .replace("{target}", &target)
+            ),
+            None => println!("{target}"),
+        }
+    }
+}
+
+pub fn PrintWhichJson(matches: &[ShortcutMatch], all: bool) -> Result<()> {
+    let payload: Vec<_> = matches
+        .iter()
+        .map(|found| {
+            serde_json::json!({
+                "keyword": found.keyword,
+                "path": found.path,
+                "remainder": found.remainder,
+                "target": WhichTarget(found),
+            })
+        })
+        .collect();
+
+    let payload = if all {
+        serde_json::Value::Array(payload)
+    } else {
+        payload
+            .into_iter()
+            .next()
+            .unwrap_or(serde_json::Value::Null)
+    };
+
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+
+    Ok(())
+}
+
+fn WhichTarget(found: &ShortcutMatch) -> String {
+    if found.remainder.is_empty() {
+        found.keyword.clone()
+    } else {
+        format!("{}/{}", found.keyword, found.remainder)
+    }
+}
+
+pub fn PrintResolvers(resolvers: &[Resolver], timeout: Duration) {
+    if resolvers.is_empty() {
+        println!(
+            "{}",
+            "No resolvers found (goto-resolve-* on PATH or resolvers= in to_zsh_config).".yellow()
+        );
+        return;
+    }
+
+    for resolver in resolvers {
+        println!(
+            "{} {} {}",
+            resolver.name.bold().cyan(),
+            "→".dimmed(),
+            resolver.path.display()
+        );
+    }
+
+    println!(
+        "{}",
+        format!("Timeout: {}ms per resolver", timeout.as_millis()).dimmed()
+    );
+}
+
+pub fn PrintResolversJson(resolvers: &[Resolver], timeout: Duration) -> Result<()> {
+    let payload: Vec<_> = resolvers
+        .iter()
+        .map(|resolver| {
+            serde_json::json!({
+                "name": resolver.name,
+                "path": resolver.path,
+                "timeout_ms": timeout.as_millis() as u64,
+            })
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string_pretty(&payload)?);
+
+    Ok(())
+}
+
+pub fn PrintOpenFile(path: &Path) {
+    println!(
+        "{} {}",
+        "Opening".green(),
+        path.display().to_string().blue()
+    );
+}
+
+pub fn PrintNoEditor(path: &Path) {
+    eprintln!(
+        "{}",
+        format!(
+            "Set $VISUAL or $EDITOR to open '{}' automatically.",
+            path.display()
+        )
+        .yellow()
+    );
+}
+
 pub fn PrintBulkAdded(keywords: &[String]) {
     if keywords.is_empty() {
         println!("{}", "No directories matched.".yellow());
@@ -279,7 +1150,7 @@ pub fn PrintRemoved(keyword: &str) {
     println!("{} {}", "Removed".green(), keyword.bold().cyan());
 }
 
-pub fn PrintJump(path: &PathBuf) {
+pub fn PrintJump(path: &Path) {
     println!(
         "{} {}",
         "Changed directory to".green(),
@@ -287,7 +1158,7 @@ pub fn PrintJump(path: &PathBuf) {
     );
 }
 
-pub fn PrintCreatedAndJumped(path: &PathBuf) {
+pub fn PrintCreatedAndJumped(path: &Path) {
     println!(
         "{} {}",
         "Created and changed directory to".green(),
@@ -295,24 +1166,20 @@ pub fn PrintCreatedAndJumped(path: &PathBuf) {
     );
 }
 
-pub fn PrintSortMode(mode: &str) {
-    println!("Sorting mode set to {}", mode.bold().cyan());
-}
-
-pub fn PrintCurrentSortMode(mode: &crate::store::SortMode) {
-    println!("Current sorting mode: {}", mode.ToLabel().bold().cyan());
+pub fn PrintConfigSet(key: &str, value: &str) {
+    if value.trim().is_empty() {
+        println!("Removed setting {}", key.bold().cyan());
+    } else {
+        println!("Set {} to {}", key.bold().cyan(), value.trim().bold());
+    }
 }
 
-trait SortModeLabel {
-    fn ToLabel(&self) -> String;
+pub fn PrintConfig(settings: &[(String, String)]) {
+    for (key, value) in settings {
+        println!("{}={}", key.bold().cyan(), value);
+    }
 }
 
-impl SortModeLabel for crate::store::SortMode {
-    fn ToLabel(&self) -> String {
-        match self {
-            crate::store::SortMode::Added => "added".to_string(),
-            crate::store::SortMode::Alpha => "alpha".to_string(),
-            crate::store::SortMode::Recent => "recent".to_string(),
-        }
-    }
+pub fn PrintCurrentSortMode(mode: &crate::store::SortMode) {
+    println!("Current sorting mode: {}", mode.Value().bold().cyan());
 }
@@ -8,6 +8,13 @@ pub struct ConfigPaths {
     pub metaFile: PathBuf,
     pub userConfigFile: PathBuf,
     pub recentFile: PathBuf,
+    pub usesFile: PathBuf,
+    pub visitFile: PathBuf,
+    pub historyFile: PathBuf,
+    pub aliasFile: PathBuf,
+    pub propsFile: PathBuf,
+    pub trustFile: PathBuf,
+    pub hooksDir: PathBuf,
 }
 
 impl ConfigPaths {
@@ -21,14 +28,56 @@ impl ConfigPaths {
         let metaFile = ResolvePath("TO_CONFIG_META_FILE", &root_str, "to_dirs_meta");
         let userConfigFile = ResolvePath("TO_USER_CONFIG_FILE", &root_str, "to_zsh_config");
         let recentFile = ResolvePath("TO_RECENT_FILE", &root_str, "to_dirs_recent");
+        let usesFile = ResolvePath("TO_USES_FILE", &root_str, "to_dirs_uses");
+        let visitFile = ResolvePath("TO_VISIT_FILE", &root_str, "to_dirs_visits");
+        let historyFile = ResolvePath("TO_HISTORY_FILE", &root_str, "to_dirs_history");
+        let aliasFile = ResolvePath("TO_ALIAS_FILE", &root_str, "to_dirs_aliases");
+        let propsFile = ResolvePath("TO_PROPS_FILE", &root_str, "to_dirs_props");
+        let trustFile = ResolvePath("TO_TRUST_FILE", &StateDir(&home).to_string_lossy(), "trust");
+        let hooksDir = ResolvePath("TO_HOOKS_DIR", &root_str, "hooks");
 
         Ok(Self {
             configFile,
             metaFile,
             userConfigFile,
             recentFile,
+            usesFile,
+            visitFile,
+            historyFile,
+            aliasFile,
+            propsFile,
+            trustFile,
+            hooksDir,
         })
     }
+
+    // The default file names under `root`, ignoring the `TO_*` overrides.
+    pub fn InDir(root: &Path) -> Self {
+        Self {
+            configFile: root.join("to_dirs"),
+            metaFile: root.join("to_dirs_meta"),
+            userConfigFile: root.join("to_zsh_config"),
+            recentFile: root.join("to_dirs_recent"),
+            usesFile: root.join("to_dirs_uses"),
+            visitFile: root.join("to_dirs_visits"),
+            historyFile: root.join("to_dirs_history"),
+            aliasFile: root.join("to_dirs_aliases"),
+            propsFile: root.join("to_dirs_props"),
+            trustFile: root.join("to_dirs_trust"),
+            hooksDir: root.join("hooks"),
+        }
+    }
+}
+
+// Machine-local state (`$XDG_STATE_HOME/goto`) that must never travel with a
+// synced `~/.goto`.
+fn StateDir(home: &str) -> PathBuf {
+    let base = match env::var("XDG_STATE_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => Path::new(home).join(".local/state"),
+    };
+
+    base.join("goto")
 }
 
 fn ResolvePath(envKey: &str, home: &str, defaultName: &str) -> PathBuf {
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateChoice {
+    Add,
+    Alias,
+    Abort,
+}
+
+// Decisions and warnings the store cannot settle on its own. The store never
+// reads stdin or prints; it asks its prompter instead.
+pub trait Prompter: Send + Sync {
+    // `path` is already saved under `existing`; add `keyword` anyway, alias it
+    // to `existing[0]`, or abort.
+    fn ConfirmDuplicatePath(
+        &self,
+        path: &Path,
+        keyword: &str,
+        existing: &[String],
+    ) -> Result<DuplicateChoice>;
+
+    // A failed hook or resolver; the operation itself carries on.
+    fn Warn(&self, _message: &str) {}
+}
+
+// Never asks: duplicate paths abort and warnings are dropped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NonInteractive;
+
+impl Prompter for NonInteractive {
+    fn ConfirmDuplicatePath(&self, _: &Path, _: &str, _: &[String]) -> Result<DuplicateChoice> {
+        Ok(DuplicateChoice::Abort)
+    }
+}
+
+// Asks on stderr and reads the answer from stdin, so stdout stays parseable
+// (e.g. with --json). Without a terminal the answer is "abort".
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn ConfirmDuplicatePath(
+        &self,
+        path: &Path,
+        keyword: &str,
+        existing: &[String],
+    ) -> Result<DuplicateChoice> {
+        eprintln!(
+            "Path '{}' is already saved under keyword(s): {}.",
+            path.display(),
+            existing.join(", ")
+        );
+
+        eprint!(
+            "Add keyword '{}' for the same path, or create an alias for '{}' instead? [y/N/a]: ",
+            keyword, existing[0]
+        );
+
+        io::stderr().flush()?;
+
+        if !io::stdin().is_terminal() {
+            return Ok(DuplicateChoice::Abort);
+        }
+
+        let mut input = String::new();
+
+        io::stdin().read_line(&mut input)?;
+
+        let choice = match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => DuplicateChoice::Add,
+            "a" | "alias" => DuplicateChoice::Alias,
+            _ => DuplicateChoice::Abort,
+        };
+
+        Ok(choice)
+    }
+
+    fn Warn(&self, message: &str) {
+        eprintln!("{}", format!("Warning: {message}").yellow());
+    }
+}
@@ -0,0 +1,257 @@
+use anyhow::{Result, bail};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const RESOLVER_PREFIX: &str = "goto-resolve-";
+
+pub const DEFAULT_RESOLVER_TIMEOUT: Duration = Duration::from_millis(2000);
+
+// Whether a store offers targets nothing else matches to resolver plugins.
+// Library stores default to `Disabled`; the CLI enables them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResolverPolicy {
+    #[default]
+    Disabled,
+    // Ask the plugins named by `resolvers=`, or every `goto-resolve-*` on PATH.
+    Enabled,
+}
+
+// A path a resolver offered. `create` is set when the plugin asked for the
+// directory to be made if it does not exist yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub create: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Resolver {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+// Resolvers in the configured order, or every `goto-resolve-*` executable on
+// PATH (first match per name wins) sorted by name when nothing is configured.
+pub fn Discover(order: &[String]) -> Vec<Resolver> {
+    if !order.is_empty() {
+        return order.iter().filter_map(|name| Locate(name)).collect();
+    }
+
+    let mut resolvers: Vec<Resolver> = Vec::new();
+
+    for dir in SearchPath() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let fileName = entry.file_name().to_string_lossy().to_string();
+
+            let Some(name) = fileName.strip_prefix(RESOLVER_PREFIX) else {
+                continue;
+            };
+
+            if name.is_empty() || resolvers.iter().any(|r| r.name == name) {
+                continue;
+            }
+
+            if IsExecutable(&entry.path()) {
+                resolvers.push(Resolver {
+                    name: name.to_string(),
+                    path: entry.path(),
+                });
+            }
+        }
+    }
+
+    resolvers.sort_by(|a, b| natord::compare(&a.name, &b.name));
+
+    resolvers
+}
+
+// Runs `resolver QUERY` and returns the candidates it printed, best first.
+// Output is either a plain path or JSON: a path string, a list of paths, or
+// objects with a `path` field and an optional `create` flag (optionally
+// wrapped in `{"candidates": [...]}`).
+pub fn Query(resolver: &Resolver, query: &str, timeout: Duration) -> Result<Vec<Candidate>> {
+    let mut child = Command::new(&resolver.path)
+        .arg(query)
+        .env("GOTO_QUERY", query)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let mut stdout = child.stdout.take();
+
+    // The reader is never joined: a process the resolver left behind can hold
+    // stdout open long after the resolver itself is gone.
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut buffer = String::new();
+
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = stdout.read_to_string(&mut buffer);
+        }
+
+        let _ = sender.send(buffer);
+    });
+
+    let deadline = Instant::now() + timeout;
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+
+            bail!("timed out after {}ms", timeout.as_millis());
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let remaining = deadline.saturating_duration_since(Instant::now());
+
+    let Ok(stdout) = receiver.recv_timeout(remaining) else {
+        bail!("timed out after {}ms", timeout.as_millis());
+    };
+
+    if !status.success() {
+        return Ok(Vec::new());
+    }
+
+    Ok(ParseCandidates(stdout.trim()))
+}
+
+// Asks each resolver in turn and returns the first usable candidate: one that
+// already exists, or else one the plugin asked to create. Failures are passed
+// to `onFailure` and skipped.
+pub fn Resolve(
+    order: &[String],
+    timeout: Duration,
+    query: &str,
+    mut onFailure: impl FnMut(&str, &str),
+) -> Option<(String, Candidate)> {
+    for resolver in Discover(order) {
+        let candidates = match Query(&resolver, query, timeout) {
+            Ok(candidates) => candidates,
+            Err(error) => {
+                onFailure(&resolver.name, &error.to_string());
+                continue;
+            }
+        };
+
+        let chosen = candidates
+            .iter()
+            .find(|candidate| candidate.path.exists())
+            .or_else(|| candidates.iter().find(|candidate| candidate.create));
+
+        if let Some(candidate) = chosen {
+            return Some((resolver.name, candidate.clone()));
+        }
+    }
+
+    None
+}
+
+fn ParseCandidates(output: &str) -> Vec<Candidate> {
+    if output.is_empty() {
+        return Vec::new();
+    }
+
+    let Ok(value) = serde_json::from_str::<Value>(output) else {
+        return output
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(|line| {
+                vec![Candidate {
+                    path: CandidatePath(line),
+                    create: false,
+                }]
+            })
+            .unwrap_or_default();
+    };
+
+    let items = match value {
+        Value::Object(mut object) => match object.remove("candidates") {
+            Some(Value::Array(items)) => items,
+            _ => vec![Value::Object(object)],
+        },
+        Value::Array(items) => items,
+        other => vec![other],
+    };
+
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Value::String(path) => Some((path.as_str(), false)),
+            Value::Object(object) => Some((
+                object.get("path").and_then(Value::as_str)?,
+                object.get("create").and_then(Value::as_bool) == Some(true),
+            )),
+            _ => None,
+        })
+        .filter(|(path, _)| !path.is_empty())
+        .map(|(path, create)| Candidate {
+            path: CandidatePath(path),
+            create,
+        })
+        .collect()
+}
+
+fn CandidatePath(raw: &str) -> PathBuf {
+    match (raw.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(raw),
+    }
+}
+
+fn Locate(name: &str) -> Option<Resolver> {
+    if name.contains('/') {
+        let path = CandidatePath(name);
+
+        let fileName = path.file_name()?.to_string_lossy().to_string();
+
+        let name = fileName
+            .strip_prefix(RESOLVER_PREFIX)
+            .unwrap_or(&fileName)
+            .to_string();
+
+        return IsExecutable(&path).then_some(Resolver { name, path });
+    }
+
+    SearchPath()
+        .into_iter()
+        .map(|dir| dir.join(format!("{RESOLVER_PREFIX}{name}")))
+        .find(|path| IsExecutable(path))
+        .map(|path| Resolver {
+            name: name.to_string(),
+            path,
+        })
+}
+
+fn SearchPath() -> Vec<PathBuf> {
+    env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+fn IsExecutable(path: &Path) -> bool {
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
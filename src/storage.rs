@@ -0,0 +1,129 @@
+use anyhow::Result;
+use fd_lock::RwLock;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Where the store keeps its `key=value` files. A backend only needs `Read` and
+// `Write`; `Append` and `Update` fall back to them unless it can do better
+// (e.g. lock the file for the whole operation).
+pub trait Storage: Send + Sync {
+    // Contents of `path`, or `None` when it does not exist yet.
+    fn Read(&self, path: &Path) -> Result<Option<String>>;
+
+    fn Write(&self, path: &Path, contents: &str) -> Result<()>;
+
+    // Adds `contents` to the end of `path` and returns the new length in bytes.
+    fn Append(&self, path: &Path, contents: &str) -> Result<u64> {
+        let mut existing = self.Read(path)?.unwrap_or_default();
+
+        existing.push_str(contents);
+
+        self.Write(path, &existing)?;
+
+        Ok(existing.len() as u64)
+    }
+
+    // Replaces `path` with `change(current contents)` without letting another
+    // writer slip in between the read and the write.
+    fn Update(&self, path: &Path, change: &mut dyn FnMut(&str) -> Result<String>) -> Result<()> {
+        let existing = self.Read(path)?.unwrap_or_default();
+
+        self.Write(path, &change(&existing)?)
+    }
+}
+
+// The files under `~/.goto`, rewritten under an exclusive lock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileStorage;
+
+impl Storage for FileStorage {
+    fn Read(&self, path: &Path) -> Result<Option<String>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn Write(&self, path: &Path, contents: &str) -> Result<()> {
+        self.Update(path, &mut |_| Ok(contents.to_string()))
+    }
+
+    fn Append(&self, path: &Path, contents: &str) -> Result<u64> {
+        let mut lock = RwLock::new(OpenFile(path, OpenOptions::new().append(true))?);
+
+        let mut guard = lock.write()?;
+
+        guard.write_all(contents.as_bytes())?;
+
+        Ok(guard.metadata()?.len())
+    }
+
+    fn Update(&self, path: &Path, change: &mut dyn FnMut(&str) -> Result<String>) -> Result<()> {
+        let mut lock = RwLock::new(OpenFile(
+            path,
+            OpenOptions::new().truncate(false).read(true).write(true),
+        )?);
+
+        let mut guard = lock.write()?;
+
+        let mut existing = String::new();
+
+        guard.read_to_string(&mut existing)?;
+
+        let contents = change(&existing)?;
+
+        guard.set_len(0)?;
+        guard.seek(SeekFrom::Start(0))?;
+        guard.write_all(contents.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+fn OpenFile(path: &Path, options: &mut OpenOptions) -> Result<File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.exists()) {
+        fs::create_dir_all(dir)?;
+    }
+
+    Ok(options.create(true).open(path)?)
+}
+
+// Keeps every file in memory; nothing touches disk. Useful for tests, previews
+// and tools that embed the store.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    files: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl MemoryStorage {
+    pub fn Contents(&self, path: &Path) -> Option<String> {
+        self.files.lock().ok()?.get(path).cloned()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn Read(&self, path: &Path) -> Result<Option<String>> {
+        Ok(self.Contents(path))
+    }
+
+    fn Write(&self, path: &Path, contents: &str) -> Result<()> {
+        self.Update(path, &mut |_| Ok(contents.to_string()))
+    }
+
+    fn Update(&self, path: &Path, change: &mut dyn FnMut(&str) -> Result<String>) -> Result<()> {
+        let mut files = self
+            .files
+            .lock()
+            .map_err(|_| anyhow::anyhow!("memory storage lock poisoned."))?;
+
+        let contents = change(files.get(path).map(String::as_str).unwrap_or_default())?;
+
+        files.insert(path.to_path_buf(), contents);
+
+        Ok(())
+    }
+}
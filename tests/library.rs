@@ -0,0 +1,317 @@
+#![allow(non_snake_case)]
+
+use goto::clock::FixedClock;
+use goto::error::GotoError;
+use goto::hooks::ScriptHooks;
+use goto::paths::ConfigPaths;
+use goto::prompt::{DuplicateChoice, Prompter};
+use goto::resolvers::ResolverPolicy;
+use goto::storage::{MemoryStorage, Storage};
+use goto::store::{AddBehavior, Store, StoreOptions};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+// Answers every duplicate path with an alias and remembers what it was asked.
+struct Recording(Mutex<Vec<String>>);
+
+impl Prompter for Recording {
+    fn ConfirmDuplicatePath(
+        &self,
+        _: &Path,
+        keyword: &str,
+        existing: &[String],
+    ) -> anyhow::Result<DuplicateChoice> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{keyword}:{}", existing.join(",")));
+
+        Ok(DuplicateChoice::Alias)
+    }
+}
+
+#[test]
+fn DateTemplatesRenderAtTheStoreClock() {
+    let temp = TempDir::new().unwrap();
+
+    let mut store = Store::LoadWith(
+        ConfigPaths::InDir(&temp.path().join(".goto")),
+        StoreOptions {
+            storage: Arc::new(MemoryStorage::default()),
+            clock: Arc::new(FixedClock(1_700_000_000)),
+            ..StoreOptions::default()
+        },
+    )
+    .unwrap();
+
+    let behavior = AddBehavior {
+        force: false,
+        assumeYes: false,
+        hostOnly: false,
+        local: false,
+    };
+
+    store
+        .AddShortcut(
+            "notes",
+            &temp.path().join("notes/{date:%Y}"),
+            None,
+            &behavior,
+        )
+        .unwrap();
+
+    assert_eq!(
+        store.ResolveJump("notes").unwrap().targetPath,
+        temp.path().join("notes/2023")
+    );
+
+    assert!(
+        store
+            .AddShortcut("bad", &temp.path().join("{date:%Q}"), None, &behavior)
+            .is_err()
+    );
+}
+
+#[test]
+fn StoreErrorsAreTyped() {
+    let temp = TempDir::new().unwrap();
+
+    let store = Store::LoadWith(
+        ConfigPaths::InDir(&temp.path().join(".goto")),
+        StoreOptions {
+            storage: Arc::new(MemoryStorage::default()),
+            ..StoreOptions::default()
+        },
+    )
+    .unwrap();
+
+    match store.ResolveJump("missing") {
+        Err(GotoError::KeywordNotFound { query, .. }) => assert_eq!(query, "missing"),
+        other => panic!("expected KeywordNotFound, got {other:?}"),
+    }
+
+    match store.ResolveHistory(None, None, 1) {
+        Err(error @ GotoError::HistoryNotFound { .. }) => {
+            assert_eq!(error.ExitCode(), 3);
+            assert_eq!(error.to_string(), "No directory history yet.");
+        }
+        other => panic!("expected HistoryNotFound, got {other:?}"),
+    }
+}
+
+#[test]
+fn MemoryStoreNeverTouchesDisk() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = temp.path().join("proj");
+    fs::create_dir_all(&proj).unwrap();
+
+    let root = temp.path().join(".goto");
+
+    let storage = Arc::new(MemoryStorage::default());
+
+    let mut store = Store::LoadWith(
+        ConfigPaths::InDir(&root),
+        StoreOptions {
+            storage: storage.clone(),
+            clock: Arc::new(FixedClock(1_000)),
+            ..StoreOptions::default()
+        },
+    )
+    .unwrap();
+
+    let behavior = AddBehavior {
+        force: false,
+        assumeYes: false,
+        hostOnly: false,
+        local: false,
+    };
+
+    store
+        .AddShortcut("proj", &proj, Some(5_000), &behavior)
+        .unwrap();
+
+    assert_eq!(
+        storage.Contents(&store.paths.metaFile).as_deref(),
+        Some("proj=5000\n")
+    );
+
+    assert!(!root.exists());
+}
+
+#[test]
+fn InjectedPrompterSettlesDuplicatePaths() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = temp.path().join("proj");
+    fs::create_dir_all(&proj).unwrap();
+
+    let storage = Arc::new(MemoryStorage::default());
+
+    let prompter = Arc::new(Recording(Mutex::new(Vec::new())));
+
+    let mut store = Store::LoadWith(
+        ConfigPaths::InDir(&temp.path().join(".goto")),
+        StoreOptions {
+            storage: storage.clone(),
+            prompter: prompter.clone(),
+            ..StoreOptions::default()
+        },
+    )
+    .unwrap();
+
+    let behavior = AddBehavior {
+        force: false,
+        assumeYes: false,
+        hostOnly: false,
+        local: false,
+    };
+
+    store.AddShortcut("proj", &proj, None, &behavior).unwrap();
+
+    store.AddShortcut("work", &proj, None, &behavior).unwrap();
+
+    assert_eq!(*prompter.0.lock().unwrap(), vec!["work:proj".to_string()]);
+
+    assert_eq!(
+        store.ResolveJump("work").unwrap().targetPath,
+        proj.canonicalize().unwrap()
+    );
+
+    assert!(
+        storage
+            .Contents(&store.paths.aliasFile)
+            .unwrap()
+            .contains("work=proj")
+    );
+}
+
+#[test]
+fn InjectedClockDrivesExpiry() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = temp.path().join("proj");
+    fs::create_dir_all(&proj).unwrap();
+
+    let paths = ConfigPaths::InDir(&temp.path().join(".goto"));
+
+    let storage = Arc::new(MemoryStorage::default());
+
+    let mut store = Store::LoadWith(
+        paths.clone(),
+        StoreOptions {
+            storage: storage.clone(),
+            clock: Arc::new(FixedClock(1_000)),
+            ..StoreOptions::default()
+        },
+    )
+    .unwrap();
+
+    let behavior = AddBehavior {
+        force: false,
+        assumeYes: false,
+        hostOnly: false,
+        local: false,
+    };
+
+    store
+        .AddShortcut("proj", &proj, Some(5_000), &behavior)
+        .unwrap();
+
+    assert!(store.ResolveJump("proj").is_ok());
+
+    let later = Store::LoadWith(
+        paths,
+        StoreOptions {
+            storage,
+            clock: Arc::new(FixedClock(6_000)),
+            ..StoreOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert!(later.ResolveJump("proj").is_err());
+}
+
+#[test]
+fn HooksAndResolversAreOffUnlessEnabled() {
+    let temp = TempDir::new().unwrap();
+
+    let proj = temp.path().join("proj");
+    fs::create_dir_all(&proj).unwrap();
+
+    let found = temp.path().join("found");
+    fs::create_dir_all(&found).unwrap();
+    let found = found.canonicalize().unwrap();
+
+    let marker = temp.path().join("hook-ran");
+    let paths = ConfigPaths::InDir(&temp.path().join(".goto"));
+    let hook = paths.hooksDir.join("on-add");
+    let resolver = temp.path().join("bin/goto-resolve-test");
+
+    for (script, body) in [
+        (&hook, format!("#!/bin/sh\ntouch '{}'\n", marker.display())),
+        (
+            &resolver,
+            format!("#!/bin/sh\necho '{}'\n", found.display()),
+        ),
+    ] {
+        fs::create_dir_all(script.parent().unwrap()).unwrap();
+
+        fs::write(script, body).unwrap();
+
+        let mut permissions = fs::metadata(script).unwrap().permissions();
+        permissions.set_mode(0o755);
+        fs::set_permissions(script, permissions).unwrap();
+    }
+
+    let storage = Arc::new(MemoryStorage::default());
+
+    storage
+        .Write(
+            &paths.userConfigFile,
+            &format!("resolvers={}\n", resolver.display()),
+        )
+        .unwrap();
+
+    let behavior = AddBehavior {
+        force: false,
+        assumeYes: false,
+        hostOnly: false,
+        local: false,
+    };
+
+    let mut quiet = Store::LoadWith(
+        paths.clone(),
+        StoreOptions {
+            storage: storage.clone(),
+            ..StoreOptions::default()
+        },
+    )
+    .unwrap();
+
+    quiet.AddShortcut("proj", &proj, None, &behavior).unwrap();
+
+    assert!(!marker.exists());
+    assert!(quiet.ResolveJump("ABC-1").is_err());
+
+    let mut enabled = Store::LoadWith(
+        paths,
+        StoreOptions {
+            storage,
+            hooks: Arc::new(ScriptHooks),
+            resolvers: ResolverPolicy::Enabled,
+            ..StoreOptions::default()
+        },
+    )
+    .unwrap();
+
+    enabled.RemoveShortcut("proj").unwrap();
+    enabled.AddShortcut("proj", &proj, None, &behavior).unwrap();
+
+    assert!(marker.exists());
+    assert_eq!(enabled.ResolveJump("ABC-1").unwrap().targetPath, found);
+}